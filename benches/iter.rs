@@ -0,0 +1,25 @@
+// Per-value cost of the full iterator, against the calculator on its own with `raw_iter`.
+
+use std::hint::black_box;
+
+use backoff_sequence::BackoffSequence;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const VALUES: usize = 1000;
+
+fn raw_against_full(c: &mut Criterion) {
+    let f = &|x: u64| x.saturating_mul(x);
+    let backoff = BackoffSequence::new(f);
+
+    let mut group = c.benchmark_group("u64");
+    group.bench_function("raw_iter", |b| {
+        b.iter(|| black_box(&backoff).raw_iter().take(VALUES).fold(0, u64::wrapping_add))
+    });
+    group.bench_function("iter", |b| {
+        b.iter(|| black_box(&backoff).iter().take(VALUES).fold(0, u64::wrapping_add))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, raw_against_full);
+criterion_main!(benches);
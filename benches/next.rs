@@ -0,0 +1,61 @@
+// The cost of `next()` for the common kinds of sequence, to catch regressions in the hot path.
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use backoff_sequence::BackoffSequence;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const VALUES: u64 = 1000;
+
+fn u64_values(c: &mut Criterion) {
+    let f = &|x: u64| x.saturating_mul(x);
+    let mut group = c.benchmark_group("u64");
+
+    let mut plain = BackoffSequence::new(f);
+    plain.max_iterations(VALUES);
+    group.bench_function("plain", |b| {
+        b.iter(|| black_box(&plain).iter().fold(0, u64::wrapping_add))
+    });
+
+    let mut clamped = BackoffSequence::new(f);
+    clamped.min(100).max(500_000).max_iterations(VALUES);
+    group.bench_function("min_max", |b| {
+        b.iter(|| black_box(&clamped).iter().fold(0, u64::wrapping_add))
+    });
+
+    let jittered = clamped.jitter_with(|v| v / 4 * 3);
+    group.bench_function("min_max_jitter", |b| {
+        b.iter(|| black_box(&jittered).iter().fold(0, u64::wrapping_add))
+    });
+    group.finish();
+}
+
+fn duration_values(c: &mut Criterion) {
+    let mut group = c.benchmark_group("duration");
+
+    let mut plain = BackoffSequence::exponential(Duration::from_millis(1), 1.01);
+    plain.max_iterations(VALUES);
+    group.bench_function("plain", |b| b.iter(|| black_box(&plain).iter().sum::<Duration>()));
+
+    let mut clamped = BackoffSequence::exponential(Duration::from_millis(1), 1.01);
+    clamped.min(Duration::from_millis(10))
+        .max(Duration::from_secs(1))
+        .max_iterations(VALUES);
+    group.bench_function("min_max", |b| b.iter(|| black_box(&clamped).iter().sum::<Duration>()));
+
+    let jittered = clamped.jitter_with(|v| v / 4 * 3);
+    group.bench_function("min_max_jitter", |b| {
+        b.iter(|| black_box(&jittered).iter().sum::<Duration>())
+    });
+    group.finish();
+}
+
+fn large_collect(c: &mut Criterion) {
+    let mut backoff = BackoffSequence::linear(1u64, 1);
+    backoff.max(1_000).max_iterations(1_000_000);
+    c.bench_function("collect_1m", |b| b.iter(|| black_box(&backoff).iter().collect::<Vec<_>>()));
+}
+
+criterion_group!(benches, u64_values, duration_values, large_collect);
+criterion_main!(benches);
@@ -0,0 +1,143 @@
+// Stand-alone versions of the sequence's `min`, `max` and `max_iterations`, for `raw_iter` or
+// any other iterator.  They don't replace the sequence's own limits, which are still fields that
+// `BackoffSequenceIterator::next` checks, and the builder methods don't apply them.
+
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+
+/// The sequence's limits as adaptors of their own, for `raw_iter` or any other iterator, so
+/// that they can be stacked in whatever order is wanted.  So
+/// `raw_iter().clamp_min(a).clamp_max(b).limit(n)` is like
+/// `min_floor(a).max(b).max_iterations(n)`, minus the jitter and the calculator being left
+/// alone once the max is reached.
+pub trait BackoffIterator: Iterator + Sized {
+    /// Lowers any value above `max` to it, along with values that can't be compared.
+    fn clamp_max(self, max: Self::Item) -> ClampMax<Self> {
+        ClampMax { inner: self, max }
+    }
+
+    /// Raises any value below `min` up to it.  Unlike the sequence's `min` nothing is skipped.
+    fn clamp_min(self, min: Self::Item) -> ClampMin<Self> {
+        ClampMin { inner: self, min }
+    }
+
+    /// Stops after `n` values, like `take` but counting in `u64` like `max_iterations`.
+    fn limit(self, n: u64) -> Limit<Self> {
+        Limit {
+            inner: self,
+            remaining: n,
+        }
+    }
+}
+
+impl<I> BackoffIterator for I where I: Iterator {}
+
+/// The iterator returned by `BackoffIterator::clamp_max`.
+#[derive(Clone, Debug)]
+pub struct ClampMax<I: Iterator> {
+    inner: I,
+    max: I::Item,
+}
+
+impl<I> Iterator for ClampMax<I>
+    where I: Iterator,
+          I::Item: PartialOrd + Clone
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.inner.next()?;
+        match value.partial_cmp(&self.max) {
+            Some(Ordering::Greater) | None => Some(self.max.clone()),
+            _ => Some(value),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// The iterator returned by `BackoffIterator::clamp_min`.
+#[derive(Clone, Debug)]
+pub struct ClampMin<I: Iterator> {
+    inner: I,
+    min: I::Item,
+}
+
+impl<I> Iterator for ClampMin<I>
+    where I: Iterator,
+          I::Item: PartialOrd + Clone
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.inner.next()?;
+        if value < self.min {
+            Some(self.min.clone())
+        } else {
+            Some(value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// The iterator returned by `BackoffIterator::limit`.
+#[derive(Clone, Debug)]
+pub struct Limit<I> {
+    inner: I,
+    remaining: u64,
+}
+
+impl<I> Iterator for Limit<I>
+    where I: Iterator
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.inner.size_hint();
+        let remaining = usize::try_from(self.remaining).unwrap_or(usize::MAX);
+        let upper = match upper {
+            Some(upper) => upper.min(remaining),
+            None => remaining,
+        };
+        (lower.min(remaining), Some(upper))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::BackoffSequence;
+
+    #[test]
+    fn stacked_adaptors() {
+        let f = &|x| x * 10;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min_floor(25).max(45).max_iterations(6);
+        let stacked = backoff.raw_iter().clamp_min(25).clamp_max(45).limit(6);
+        assert_eq!(stacked.size_hint(), (6, Some(6)));
+        assert_eq!(stacked.collect::<Vec<_>>(), backoff.iter().collect::<Vec<_>>());
+
+        // the order matters once the bounds cross
+        let v = backoff.raw_iter().clamp_min(50).clamp_max(30).limit(2).collect::<Vec<_>>();
+        assert_eq!(v, vec![30, 30]);
+        let v = backoff.raw_iter().clamp_max(30).clamp_min(50).limit(2).collect::<Vec<_>>();
+        assert_eq!(v, vec![50, 50]);
+
+        let v = vec![1.0, f64::NAN].into_iter().clamp_max(0.5).collect::<Vec<_>>();
+        assert_eq!(v, vec![0.5, 0.5]);
+        assert_eq!(vec![1, 2].into_iter().limit(5).size_hint(), (2, Some(2)));
+    }
+}
@@ -0,0 +1,79 @@
+// async-std integration, enabled with the `async-std` feature.
+
+use std::future::Future;
+use std::time::Duration;
+
+use ::async_std::task::sleep;
+
+use crate::{retry, retry_async};
+use crate::{BackoffSequence, Calculator, Jitter};
+
+/// Async retries that wait using `async_std::task::sleep`.
+pub trait RetryAsync {
+    /// The async version of `BackoffSequence::retry`: awaits `op` until it succeeds or the
+    /// sequence runs out, sleeping on the async-std timer between attempts.
+    fn retry_async<T, E, Fut, O>(&self, op: O) -> impl Future<Output = Result<T, E>>
+        where O: FnMut() -> Fut,
+              Fut: Future<Output = Result<T, E>>;
+}
+
+impl<'a, F, J> RetryAsync for BackoffSequence<'a, F, Duration, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    async fn retry_async<T, E, Fut, O>(&self, op: O) -> Result<T, E>
+        where O: FnMut() -> Fut,
+              Fut: Future<Output = Result<T, E>>
+    {
+        retry_async::retry(self, Some(self.time()), op, sleep, retry::ignore).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[::async_std::test]
+    async fn retries_until_success() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(5);
+
+        let mut calls = 0;
+        let res = backoff.retry_async(|| {
+                calls += 1;
+                let c = calls;
+                async move { if c < 3 { Err(c) } else { Ok(c) } }
+            })
+            .await;
+
+        assert_eq!(res, Ok(3));
+    }
+
+    #[::async_std::test]
+    async fn sleeps_between_attempts_only() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(4);
+
+        let attempts = Cell::new(0);
+        let sleeps = Cell::new(0);
+        let res = retry_async::retry(&backoff,
+                                     None,
+                                     || {
+                                         attempts.set(attempts.get() + 1);
+                                         async { Err::<(), _>(()) }
+                                     },
+                                     |d| {
+                                         sleeps.set(sleeps.get() + 1);
+                                         sleep(d)
+                                     },
+                                     retry::ignore)
+            .await;
+
+        assert_eq!(res, Err(()));
+        assert_eq!(attempts.get(), 4);
+        assert_eq!(sleeps.get(), attempts.get() - 1);
+    }
+}
@@ -0,0 +1,97 @@
+use alloc::sync::Arc;
+
+use super::{BackoffError, BackoffSequence, CalculatorHandle};
+
+/// Configures a `BackoffSequence` in any order, taking the calculator by value so there's no
+/// need to bind a closure to a variable first.  Created by `BackoffSequence::builder()`.
+pub struct BackoffSequenceBuilder<F, B> {
+    max_iterations: Option<u64>,
+    min_value: Option<B>,
+    max_value: Option<B>,
+    calculator: Option<Arc<F>>,
+}
+
+impl<F, B> BackoffSequence<'static, F, B> {
+    pub fn builder() -> BackoffSequenceBuilder<F, B> {
+        BackoffSequenceBuilder {
+            max_iterations: None,
+            min_value: None,
+            max_value: None,
+            calculator: None,
+        }
+    }
+}
+
+impl<F, B> BackoffSequenceBuilder<F, B>
+    where F: Fn(u64) -> B,
+          B: PartialOrd + Clone
+{
+    pub fn calculator(&mut self, f: F) -> &mut Self {
+        self.calculator = Some(Arc::new(f));
+        self
+    }
+
+    pub fn max_iterations(&mut self, x: u64) -> &mut Self {
+        self.max_iterations = Some(x);
+        self
+    }
+
+    pub fn min(&mut self, x: B) -> &mut Self {
+        self.min_value = Some(x);
+        self
+    }
+
+    pub fn max(&mut self, x: B) -> &mut Self {
+        self.max_value = Some(x);
+        self
+    }
+
+    /// The configured sequence, or an error if no calculator was ever provided.
+    pub fn build(&self) -> Result<BackoffSequence<'static, F, B>, BackoffError> {
+        let calculator = self.calculator.clone().ok_or(BackoffError::MissingCalculator)?;
+
+        let mut seq = BackoffSequence::with_calculator(CalculatorHandle::Shared(calculator));
+        seq.max_iterations = self.max_iterations;
+        seq.min_value = self.min_value.clone();
+        seq.max_value = self.max_value.clone();
+        Ok(seq)
+    }
+
+    /// Like `build`, but also returns an error if the sequence fails
+    /// `BackoffSequence::validate`.
+    pub fn try_build(&self) -> Result<BackoffSequence<'static, F, B>, BackoffError> {
+        let seq = self.build()?;
+        seq.validate()?;
+        Ok(seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calculator_set_last() {
+        let seq = BackoffSequence::builder()
+            .max(150)
+            .max_iterations(4)
+            .calculator(|x| 10u64.pow(x as u32) - 1)
+            .build()
+            .unwrap();
+        assert_eq!(seq.iter().collect::<Vec<_>>(), vec![9, 99, 150, 150]);
+    }
+
+    #[test]
+    fn missing_calculator() {
+        let res = BackoffSequence::<fn(u64) -> u64, u64>::builder().max_iterations(4).build();
+        assert_eq!(res.err(), Some(BackoffError::MissingCalculator));
+    }
+
+    #[test]
+    fn validated() {
+        let mut builder = BackoffSequence::builder();
+        builder.calculator(|x| x).min(5).max(4);
+        assert!(builder.build().is_ok());
+        assert_eq!(builder.try_build().err(), Some(BackoffError::MinAboveMax));
+    }
+}
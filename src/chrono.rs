@@ -0,0 +1,138 @@
+// `chrono::Duration` support, enabled with the `chrono` feature.  The arithmetic traits let the
+// presets and jitter work with it, and the retry helpers turn each value into a
+// `std::time::Duration` to sleep for.
+
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use ::chrono::Duration as ChronoDuration;
+
+use crate::{BackoffSequence, Bounded, Calculator, Exponential, Halve, Jitter, RetryOutcome,
+            SaturatingAdd, SaturatingMul, Scale};
+
+impl Halve for ChronoDuration {
+    fn halve(&self) -> Self {
+        *self / 2
+    }
+}
+
+impl SaturatingAdd for ChronoDuration {
+    fn saturating_add(&self, rhs: &Self) -> Self {
+        self.checked_add(rhs).unwrap_or(if *rhs < ChronoDuration::zero() {
+            ChronoDuration::MIN
+        } else {
+            ChronoDuration::MAX
+        })
+    }
+}
+
+impl SaturatingMul for ChronoDuration {
+    fn saturating_mul(&self, rhs: u64) -> Self {
+        let zero = ChronoDuration::zero();
+        match i32::try_from(rhs).ok().and_then(|rhs| self.checked_mul(rhs)) {
+            Some(product) => product,
+            None if *self == zero => zero,
+            None if *self > zero => ChronoDuration::MAX,
+            None => ChronoDuration::MIN,
+        }
+    }
+}
+
+impl Scale for ChronoDuration {
+    fn scale(&self, factor: f64) -> Self {
+        let secs = self.as_seconds_f64() * factor;
+        // to the nanosecond while that fits in an i64, and to the millisecond past that
+        let nanos = secs * 1e9;
+        if nanos.abs() < i64::MAX as f64 {
+            return ChronoDuration::nanoseconds(nanos as i64);
+        }
+        ChronoDuration::try_milliseconds((secs * 1e3) as i64).unwrap_or(if secs > 0.0 {
+            ChronoDuration::MAX
+        } else {
+            ChronoDuration::MIN
+        })
+    }
+}
+
+impl Bounded for ChronoDuration {
+    fn max_value() -> Self {
+        ChronoDuration::MAX
+    }
+}
+
+impl BackoffSequence<'static, Exponential<ChronoDuration>, ChronoDuration> {
+    /// `exponential` for `chrono::Duration`.  A negative `initial` makes every value negative,
+    /// which the retry helpers sleep for as zero.
+    pub fn exponential_chrono(initial: ChronoDuration, factor: f64) -> Self {
+        BackoffSequence::exponential(initial, factor)
+    }
+}
+
+// how long to really sleep for; chrono durations can be negative, but sleeps can't
+fn to_std(delay: ChronoDuration) -> Duration {
+    delay.to_std().unwrap_or(Duration::ZERO)
+}
+
+impl<'a, F, J> BackoffSequence<'a, F, ChronoDuration, J>
+    where F: Calculator<ChronoDuration>,
+          J: Jitter<ChronoDuration> + Clone
+{
+    /// `BackoffSequence::retry` for `chrono::Duration`, sleeping for each value between
+    /// attempts.  Negative values don't sleep at all.
+    pub fn retry<T, E, O>(&self, op: O) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.retry_verbose(op).result
+    }
+
+    /// Like `retry`, but reports the number of attempts and the total of the values waited.
+    pub fn retry_verbose<T, E, O>(&self, op: O) -> RetryOutcome<T, E, ChronoDuration>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.retry_with(op, |_| true, |_, _, _| (), |delay| self.time().sleep(to_std(delay)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockClock;
+
+    #[test]
+    fn arithmetic() {
+        let second = ChronoDuration::seconds(1);
+        assert_eq!(second.halve(), ChronoDuration::milliseconds(500));
+        assert_eq!(second.scale(1.5), ChronoDuration::milliseconds(1500));
+        assert_eq!(second.scale(-2.0), ChronoDuration::seconds(-2));
+        assert_eq!(ChronoDuration::MAX.scale(2.0), ChronoDuration::MAX);
+        assert_eq!(ChronoDuration::MAX.saturating_add(&second), ChronoDuration::MAX);
+        assert_eq!(ChronoDuration::MIN.saturating_add(&-second), ChronoDuration::MIN);
+        assert_eq!(second.saturating_mul(u64::MAX), ChronoDuration::MAX);
+        assert_eq!((-second).saturating_mul(u64::MAX), ChronoDuration::MIN);
+    }
+
+    #[test]
+    fn exponential_preset() {
+        let mut backoff = BackoffSequence::exponential_chrono(ChronoDuration::milliseconds(100),
+                                                              2.0);
+        backoff.max(ChronoDuration::milliseconds(300));
+        assert_eq!(backoff.iter().take(3).collect::<Vec<_>>(),
+                   vec![ChronoDuration::milliseconds(100),
+                        ChronoDuration::milliseconds(200),
+                        ChronoDuration::milliseconds(300)]);
+    }
+
+    #[test]
+    fn retry_sleeps_negative_as_zero() {
+        let clock = MockClock::new();
+        let f = &|i| ChronoDuration::seconds(2 - i as i64);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(4).clock(clock.clone());
+
+        let outcome = backoff.retry_verbose(|| Err::<(), _>(()));
+        assert_eq!(outcome.attempts, 4);
+        assert_eq!(outcome.total_wait, ChronoDuration::zero());
+        // 1s, then 0s, then -1s which sleeps for nothing
+        assert_eq!(clock.elapsed(), Duration::from_secs(1));
+    }
+}
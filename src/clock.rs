@@ -0,0 +1,141 @@
+// Where the blocking helpers get the time from, so that tests can swap in a clock that doesn't
+// really wait.
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::BackoffSequence;
+
+/// The current time, and a way to wait for time to pass.  The blocking retry helpers,
+/// `sleep_iter` and `max_elapsed_from` all go through the clock set with
+/// `BackoffSequence::clock`.  The async helpers get the time for `on_retry` from it too, but
+/// sleep on their runtime's timer, and on wasm they don't read the time at all.
+pub trait Clock {
+    fn now(&self) -> Instant;
+    fn sleep(&self, duration: Duration);
+}
+
+// the clock set by `BackoffSequence::clock`, which clones of the sequence share
+pub(crate) type SharedClock = Arc<dyn Clock + Send + Sync>;
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J> {
+    /// Uses `clock` for the time and for sleeping, instead of the `SystemClock`.  This is mostly
+    /// for tests, which can use a `MockClock` to retry without really waiting.
+    pub fn clock<C>(&mut self, clock: C) -> &mut Self
+        where C: Clock + Send + Sync + 'static
+    {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    // the clock to use, falling back to the real one
+    pub(crate) fn time(&self) -> &(dyn Clock + Send + Sync) {
+        match self.clock {
+            Some(ref clock) => &**clock,
+            None => &SystemClock,
+        }
+    }
+}
+
+/// The real clock, and the default: `Instant::now` and `thread::sleep`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+/// A clock for tests, where sleeping moves time forward straight away instead of waiting.
+/// Clones share the same time, so keep one to look at after handing the other to a sequence.
+#[derive(Clone, Debug)]
+pub struct MockClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        MockClock {
+            start: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// Moves time forward by `duration`, as sleeping does.
+    pub fn advance(&self, duration: Duration) {
+        let mut elapsed = self.elapsed.lock().unwrap_or_else(PoisonError::into_inner);
+        *elapsed = elapsed.saturating_add(duration);
+    }
+
+    /// How far time has moved since the clock was made.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        MockClock::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        let elapsed = self.elapsed();
+        self.start.checked_add(elapsed).unwrap_or_else(|| furthest(self.start, elapsed))
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration)
+    }
+}
+
+// an `Instant` can't go as far as `Duration::MAX`, so once a mock clock's been moved past the
+// furthest one there is, time stops there
+fn furthest(start: Instant, elapsed: Duration) -> Instant {
+    let (mut reachable, mut unreachable) = (Duration::ZERO, elapsed);
+    while unreachable - reachable > Duration::from_nanos(1) {
+        let mid = reachable + (unreachable - reachable) / 2;
+        if start.checked_add(mid).is_some() {
+            reachable = mid;
+        } else {
+            unreachable = mid;
+        }
+    }
+    start + reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_time_moves_on_sleep() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        clock.sleep(Duration::from_secs(3600));
+        clock.clone().advance(Duration::from_secs(1));
+        assert_eq!(clock.now() - start, Duration::from_secs(3601));
+        assert_eq!(clock.elapsed(), Duration::from_secs(3601));
+    }
+
+    #[test]
+    fn mock_time_stops_at_the_furthest_instant() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        clock.advance(Duration::MAX);
+        let end = clock.now();
+        assert!(end > start);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(clock.now(), end);
+        assert_eq!(clock.elapsed(), Duration::MAX);
+    }
+}
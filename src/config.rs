@@ -0,0 +1,274 @@
+// Backoff described as plain data rather than a closure and builder calls, so that it can be
+// loaded from a config file.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "rand")]
+use super::FactorJitter;
+#[cfg(not(feature = "rand"))]
+use super::NoJitter;
+use super::ops;
+use super::{BackoffError, BackoffSequence, Calculator, OwnedBackoffSequence, SaturatingAdd,
+            SaturatingMul, Scale};
+
+/// The `min`, `max` and `max_iterations` of a sequence, which unlike the calculator can be
+/// compared, printed and (with the `serde` feature) serialized.  Any of them can be left out of
+/// a config file.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Limits<B> {
+    pub max_iterations: Option<u64>,
+    pub min_value: Option<B>,
+    pub max_value: Option<B>,
+}
+
+impl<B> Limits<B> {
+    /// Limits with nothing set.
+    pub fn new() -> Self {
+        Limits {
+            max_iterations: None,
+            min_value: None,
+            max_value: None,
+        }
+    }
+}
+
+impl<B> Default for Limits<B> {
+    fn default() -> Self {
+        Limits::new()
+    }
+}
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where B: Clone
+{
+    /// The sequence's `min`, `max` and `max_iterations`.  Whether the min skips or is only a
+    /// floor isn't kept, and nor is a `max_with` cap.
+    pub fn limits(&self) -> Limits<B> {
+        Limits {
+            max_iterations: self.max_iterations,
+            min_value: self.min_value.clone(),
+            max_value: self.max_value.clone(),
+        }
+    }
+
+    /// Sets whichever of `limits` are there, like calling `min`, `max` and `max_iterations`
+    /// with them.  Anything that's `None` is left as it was.
+    pub fn set_limits(&mut self, limits: &Limits<B>) -> &mut Self {
+        if let Some(x) = limits.max_iterations {
+            self.max_iterations = Some(x);
+        }
+        if let Some(ref x) = limits.min_value {
+            self.min_value = Some(x.clone());
+            self.min_skip = true;
+        }
+        if let Some(ref x) = limits.max_value {
+            self.max_value = Some(x.clone());
+            self.max_fn = None;
+        }
+        self
+    }
+}
+
+/// One of the preset shapes of backoff, by name and parameters.  Unlike a closure this can be
+/// compared, printed and (with the `serde` feature) serialized.  It's also a calculator itself.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Strategy<B> {
+    /// Like `BackoffSequence::exponential`.
+    Exponential { initial: B, factor: f64 },
+    /// Like `BackoffSequence::linear`.
+    Linear { initial: B, increment: B },
+    /// Like `BackoffSequence::constant`.
+    Constant { value: B },
+}
+
+impl<B> Calculator<B> for Strategy<B>
+    where B: Scale + SaturatingAdd + SaturatingMul + Clone
+{
+    fn calculate(&self, iteration: u64) -> B {
+        let steps = iteration.saturating_sub(1);
+        match self {
+            Strategy::Exponential { initial, factor } => initial.scale(ops::powi(*factor, steps)),
+            Strategy::Linear { initial, increment } => {
+                initial.saturating_add(&increment.saturating_mul(steps))
+            }
+            Strategy::Constant { value } => value.clone(),
+        }
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some(match self {
+            Strategy::Exponential { .. } => "exponential",
+            Strategy::Linear { .. } => "linear",
+            Strategy::Constant { .. } => "constant",
+        })
+    }
+}
+
+/// Everything needed to make a sequence: the strategy, and the limits and `jitter` to go with
+/// it.  All but the strategy can be left out of a config file.  `jitter` is the `p` passed to
+/// `jitter_factor`, so it needs the `rand` feature to be used.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BackoffConfig<B> {
+    pub strategy: Strategy<B>,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub limits: Limits<B>,
+    pub jitter: Option<f64>,
+}
+
+/// The jitter that `BackoffConfig::build` puts on a sequence, which is only ever there with the
+/// `rand` feature.
+#[cfg(feature = "rand")]
+pub type ConfigJitter = Option<FactorJitter>;
+/// The jitter that `BackoffConfig::build` puts on a sequence, which is only ever there with the
+/// `rand` feature.
+#[cfg(not(feature = "rand"))]
+pub type ConfigJitter = NoJitter;
+
+impl<B> BackoffConfig<B> {
+    /// A config for `strategy` with no limits set.
+    pub fn new(strategy: Strategy<B>) -> Self {
+        BackoffConfig {
+            strategy,
+            limits: Limits::new(),
+            jitter: None,
+        }
+    }
+}
+
+impl<B> BackoffConfig<B>
+    where B: Scale + SaturatingAdd + SaturatingMul + PartialOrd + Clone + 'static
+{
+    /// A sequence set up as this config describes, apart from `jitter`.
+    pub fn into_sequence(&self) -> OwnedBackoffSequence<Strategy<B>, B> {
+        let mut seq = BackoffSequence::shared(self.strategy.clone());
+        seq.set_limits(&self.limits);
+        OwnedBackoffSequence::from(seq)
+    }
+
+    /// A sequence set up as this config describes, including `jitter` if it's set.  Without the
+    /// `rand` feature there's no jitter to use, so a config with one is an error.
+    pub fn build(&self)
+                 -> Result<OwnedBackoffSequence<Strategy<B>, B, ConfigJitter>, BackoffError> {
+        let seq = self.into_sequence();
+        #[cfg(feature = "rand")]
+        let jitter = self.jitter.map(|p| seq.jitter_factor(p).jitter);
+        #[cfg(not(feature = "rand"))]
+        let jitter = match self.jitter {
+            Some(_) => return Err(BackoffError::JitterUnavailable),
+            None => NoJitter,
+        };
+        Ok(OwnedBackoffSequence::from(seq.with_jitter(jitter)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn set_and_read_back() {
+        let f = &|x| x * 10;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(9);
+        assert_eq!(backoff.limits(), Limits {
+            max_iterations: Some(9),
+            ..Limits::new()
+        });
+
+        let mut limits = Limits::new();
+        limits.min_value = Some(25);
+        limits.max_value = Some(45);
+        backoff.set_limits(&limits);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![30, 40, 45, 45, 45, 45, 45, 45, 45]);
+        assert_eq!(backoff.limits().max_iterations, Some(9));
+    }
+
+    #[test]
+    fn strategies() {
+        let mut config = BackoffConfig::new(Strategy::Exponential {
+            initial: 100u64,
+            factor: 2.0,
+        });
+        config.limits.max_value = Some(500);
+        config.limits.max_iterations = Some(4);
+        assert_eq!(config.into_sequence().iter().collect::<Vec<_>>(), vec![100, 200, 400, 500]);
+
+        let mut config = BackoffConfig::new(Strategy::Linear {
+            initial: Duration::from_millis(10),
+            increment: Duration::from_millis(5),
+        });
+        config.limits.min_value = Some(Duration::from_millis(18));
+        config.limits.max_iterations = Some(2);
+        assert_eq!(config.build().unwrap().iter().collect::<Vec<_>>(),
+                   vec![Duration::from_millis(20), Duration::from_millis(25)]);
+
+        let config = BackoffConfig::new(Strategy::Constant { value: 7i32 });
+        assert_eq!(config.into_sequence().iter().take(2).collect::<Vec<_>>(), vec![7, 7]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let limits: Limits<u64> = serde_json::from_str(r#"{"max_value":175}"#).unwrap();
+        assert_eq!(limits.max_value, Some(175));
+        assert_eq!(limits.min_value, None);
+
+        let limits = Limits {
+            max_iterations: Some(5),
+            min_value: Some(Duration::from_millis(100)),
+            max_value: None,
+        };
+        let json = serde_json::to_string(&limits).unwrap();
+        assert_eq!(serde_json::from_str::<Limits<_>>(&json).unwrap(), limits);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_json_round_trip() {
+        let json = r#"{"strategy":{"linear":{"initial":100,"increment":50}},"max_value":175}"#;
+        let config: BackoffConfig<u64> = serde_json::from_str(json).unwrap();
+        assert_eq!(config.strategy, Strategy::Linear {
+            initial: 100,
+            increment: 50,
+        });
+        assert_eq!(config.limits.max_value, Some(175));
+        assert_eq!(config.limits.min_value, None);
+        assert_eq!(config.into_sequence().iter().take(3).collect::<Vec<_>>(),
+                   vec![100, 150, 175]);
+
+        let mut config = BackoffConfig::new(Strategy::Exponential {
+            initial: Duration::from_millis(100),
+            factor: 1.5,
+        });
+        config.limits.max_iterations = Some(5);
+        config.jitter = Some(0.1);
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<BackoffConfig<Duration>>(&json).unwrap(), config);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn build_with_jitter() {
+        let mut config = BackoffConfig::new(Strategy::Constant { value: 1000u64 });
+        config.limits.max_iterations = Some(20);
+        assert!(config.build().unwrap().iter().all(|v| v == 1000));
+
+        config.jitter = Some(0.2);
+        assert!(config.build().unwrap().iter().all(|v| (800..=1200).contains(&v)));
+    }
+
+    #[cfg(not(feature = "rand"))]
+    #[test]
+    fn jitter_needs_rand() {
+        let mut config = BackoffConfig::new(Strategy::Constant { value: 1000u64 });
+        assert!(config.build().is_ok());
+        config.jitter = Some(0.2);
+        assert_eq!(config.build().err(), Some(BackoffError::JitterUnavailable));
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+use super::{BackoffSequence, BackoffSequenceIterator, Calculator, Clock, Jitter, NoJitter};
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
+{
+    /// An iterator that ends once more than `budget` of time has passed on the sequence's clock
+    /// since `start`.
+    /// The clock is checked on every call to `next()`, so time spent in the operation being
+    /// retried counts towards the budget as well as the sleeps, unlike `total_duration`.
+    pub fn max_elapsed_from(&self, start: Instant, budget: Duration) -> MaxElapsed<'_, F, B, J> {
+        MaxElapsed {
+            inner: self.iter(),
+            start,
+            budget,
+            clock: self.time(),
+        }
+    }
+}
+
+/// The iterator returned by `BackoffSequence::max_elapsed_from`.
+pub struct MaxElapsed<'a, F: 'a, B, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+    start: Instant,
+    budget: Duration,
+    clock: &'a (dyn Clock + Send + Sync),
+}
+
+impl<'a, F, B, J> Iterator for MaxElapsed<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        if self.clock.now().saturating_duration_since(self.start) > self.budget {
+            return None;
+        }
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockClock;
+    use std::thread::sleep;
+
+    #[test]
+    fn stops_after_budget() {
+        let f = &|i| i;
+        let backoff = BackoffSequence::new(f);
+
+        let mut iter = backoff.max_elapsed_from(Instant::now(), Duration::from_millis(20));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(2));
+        sleep(Duration::from_millis(30));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn mock_clock_budget() {
+        let clock = MockClock::new();
+        let f = &|i| Duration::from_secs(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.clock(clock.clone());
+
+        let slept = backoff.max_elapsed_from(clock.now(), Duration::from_secs(5))
+            .inspect(|&d| clock.sleep(d))
+            .count();
+        // sleeps of 1, 2 and 3 seconds use up the budget
+        assert_eq!(slept, 3);
+    }
+
+    #[test]
+    fn start_in_the_past() {
+        let f = &|i| i;
+        let backoff = BackoffSequence::new(f);
+        let start = Instant::now() - Duration::from_secs(60);
+
+        assert_eq!(backoff.max_elapsed_from(start, Duration::from_secs(30)).next(), None);
+        assert_eq!(backoff.max_elapsed_from(start, Duration::from_secs(90)).next(), Some(1));
+    }
+}
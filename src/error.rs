@@ -0,0 +1,36 @@
+use core::error::Error;
+use core::fmt;
+
+/// Everything that can go wrong while putting a `BackoffSequence` together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackoffError {
+    /// `build()` was called before a calculator was set.
+    MissingCalculator,
+    /// `min` is greater than `max`, so the max will win.
+    MinAboveMax,
+    /// `max_iterations` is 0, so the sequence is empty.
+    NoIterations,
+    /// The calculator's first value can't be compared, like a float's NaN.
+    UnorderedValue,
+    /// Something that needs the sequence to end was given one without `max_iterations`.
+    Unbounded,
+    /// A config asked for jitter without the `rand` feature to provide it.
+    JitterUnavailable,
+}
+
+impl fmt::Display for BackoffError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BackoffError::MissingCalculator => write!(f, "no calculator was provided"),
+            BackoffError::MinAboveMax => write!(f, "the min is greater than the max"),
+            BackoffError::NoIterations => write!(f, "max_iterations is 0"),
+            BackoffError::UnorderedValue => {
+                write!(f, "the calculator's first value can't be compared with anything")
+            }
+            BackoffError::Unbounded => write!(f, "max_iterations isn't set"),
+            BackoffError::JitterUnavailable => write!(f, "jitter needs the rand feature"),
+        }
+    }
+}
+
+impl Error for BackoffError {}
@@ -0,0 +1,216 @@
+// What the retry loops report as they go.  With the `log` feature these go to the `log` crate,
+// and with the `tracing` feature each attempt gets a span, both under the `backoff_sequence`
+// target.  Without either they're empty and compile away to nothing.
+
+use std::fmt::Debug;
+#[cfg(any(feature = "tokio", feature = "async-std", feature = "wasm"))]
+use std::future::Future;
+
+/// Attempt number `attempt` failed, and the next one follows after `delay`.
+#[cfg(feature = "log")]
+pub(crate) fn retrying<B: Debug>(attempt: u64, delay: &B) {
+    log::debug!(target: "backoff_sequence",
+                "attempt {} failed, retrying in {:?}",
+                attempt,
+                delay);
+}
+
+#[cfg(not(feature = "log"))]
+pub(crate) fn retrying<B: Debug>(_: u64, _: &B) {}
+
+/// Attempt number `attempt` failed, and there won't be another one.
+#[cfg(feature = "log")]
+pub(crate) fn giving_up(attempt: u64) {
+    log::warn!(target: "backoff_sequence", "attempt {} failed, giving up", attempt);
+}
+
+#[cfg(not(feature = "log"))]
+pub(crate) fn giving_up(_: u64) {}
+
+/// One call of the operation being retried.  With the `tracing` feature this is an `attempt`
+/// span, with the `attempt` number, the `delay` waited before it (empty for the first attempt),
+/// and whether it `succeeded`.
+pub(crate) struct Attempt {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Attempt {
+    #[cfg(feature = "tracing")]
+    pub(crate) fn start<B: Debug>(attempt: u64, delay: Option<&B>) -> Self {
+        let span = tracing::info_span!(target: "backoff_sequence",
+                                       "attempt",
+                                       attempt,
+                                       delay = tracing::field::Empty,
+                                       succeeded = tracing::field::Empty);
+        if let Some(delay) = delay {
+            span.record("delay", tracing::field::debug(delay));
+        }
+        Attempt { span }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn start<B: Debug>(_: u64, _: Option<&B>) -> Self {
+        Attempt {}
+    }
+
+    pub(crate) fn run<T, E, O>(&self, op: O) -> Result<T, E>
+        where O: FnOnce() -> Result<T, E>
+    {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.enter();
+        let result = op();
+        #[cfg(feature = "tracing")]
+        self.span.record("succeeded", result.is_ok());
+        result
+    }
+
+    #[cfg(any(feature = "tokio", feature = "async-std", feature = "wasm"))]
+    pub(crate) async fn run_async<T, E, Fut>(&self, fut: Fut) -> Result<T, E>
+        where Fut: Future<Output = Result<T, E>>
+    {
+        #[cfg(feature = "tracing")]
+        let result = tracing::Instrument::instrument(fut, self.span.clone()).await;
+        #[cfg(not(feature = "tracing"))]
+        let result = fut.await;
+        #[cfg(feature = "tracing")]
+        self.span.record("succeeded", result.is_ok());
+        result
+    }
+}
+
+#[cfg(all(test, any(feature = "log", feature = "tracing")))]
+mod tests {
+    #[cfg(feature = "log")]
+    use std::cell::RefCell;
+    #[cfg(feature = "tracing")]
+    use std::fmt::Debug;
+    #[cfg(feature = "tracing")]
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[cfg(feature = "tracing")]
+    use tracing::field::{Field, Visit};
+    #[cfg(feature = "tracing")]
+    use tracing::span::{Attributes, Id, Record};
+    #[cfg(feature = "tracing")]
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::BackoffSequence;
+
+    // the logger is global, so only keep what this test's own thread logs
+    #[cfg(feature = "log")]
+    thread_local! {
+        static RECORDS: RefCell<Vec<(log::Level, String)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[cfg(feature = "log")]
+    struct Capture;
+
+    #[cfg(feature = "log")]
+    impl log::Log for Capture {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "backoff_sequence"
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                let entry = (record.level(), record.args().to_string());
+                RECORDS.with(|records| records.borrow_mut().push(entry));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn logs_each_attempt() {
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let f = &|i| Duration::from_millis(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+        assert_eq!(backoff.retry(|| Err::<(), _>("nope")), Err("nope"));
+
+        assert_eq!(RECORDS.with(|records| records.take()),
+                   vec![(log::Level::Debug, "attempt 1 failed, retrying in 1ms".to_string()),
+                        (log::Level::Debug, "attempt 2 failed, retrying in 2ms".to_string()),
+                        (log::Level::Warn, "attempt 3 failed, giving up".to_string())]);
+    }
+
+    // just enough of a subscriber to see which spans were made, and what went in them
+    #[cfg(feature = "tracing")]
+    type SpanFields = Vec<(String, String)>;
+
+    #[cfg(feature = "tracing")]
+    #[derive(Clone, Default)]
+    struct Spans(Arc<Mutex<Vec<SpanFields>>>);
+
+    #[cfg(feature = "tracing")]
+    struct Fields<'a>(&'a mut SpanFields);
+
+    #[cfg(feature = "tracing")]
+    impl<'a> Visit for Fields<'a> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl Subscriber for Spans {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes) -> Id {
+            let mut spans = self.0.lock().unwrap();
+            let mut fields = vec![("name".to_string(), span.metadata().name().to_string())];
+            span.record(&mut Fields(&mut fields));
+            spans.push(fields);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record) {
+            let mut spans = self.0.lock().unwrap();
+            values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1]));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn span_for_each_attempt() {
+        let spans = Spans::default();
+        let f = &|i| Duration::from_millis(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let mut calls = 0;
+        let res = tracing::subscriber::with_default(spans.clone(), || {
+            backoff.retry(|| {
+                calls += 1;
+                if calls < 2 { Err(calls) } else { Ok(calls) }
+            })
+        });
+        assert_eq!(res, Ok(2));
+
+        let field = |name: &str, value: &str| (name.to_string(), value.to_string());
+        assert_eq!(*spans.0.lock().unwrap(),
+                   vec![vec![field("name", "attempt"),
+                             field("attempt", "1"),
+                             field("succeeded", "false")],
+                        vec![field("name", "attempt"),
+                             field("attempt", "2"),
+                             field("delay", "1ms"),
+                             field("succeeded", "true")]]);
+    }
+}
@@ -0,0 +1,457 @@
+#[cfg(feature = "rand")]
+use core::ops::Sub;
+
+#[cfg(feature = "rand")]
+use rand::distributions::uniform::SampleUniform;
+#[cfg(feature = "rand")]
+use rand::rngs::{OsRng, StdRng};
+#[cfg(feature = "rand")]
+use rand::{Rng, SeedableRng};
+
+#[cfg(feature = "rand")]
+use super::{BackoffSequence, Calculator, Halve, SaturatingMul, Scale};
+
+/// A step in the sequence that randomizes each value.  `previous` is the value the iterator
+/// yielded last time, if there was one.  Each iterator gets its own clone of the jitter, so any
+/// state it keeps starts over with every call to `iter()`.
+///
+/// Jitter is the last thing to happen to a value: the calculator runs first, then the `min`
+/// skip or floor, then the `max` clamp.  The jittered value is then clamped to `min` and `max`
+/// once more, so that jitter can never push it out of bounds.
+pub trait Jitter<B> {
+    fn jitter(&mut self, value: B, previous: Option<&B>) -> B;
+}
+
+/// The default, which leaves every value alone.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NoJitter;
+
+impl<B> Jitter<B> for NoJitter {
+    fn jitter(&mut self, value: B, _: Option<&B>) -> B {
+        value
+    }
+}
+
+/// Jitter from a plain closure, created by `BackoffSequence::jitter_with`.
+#[derive(Clone, Copy, Debug)]
+pub struct JitterWith<G>(pub G);
+
+impl<B, G> Jitter<B> for JitterWith<G>
+    where G: Fn(B) -> B
+{
+    fn jitter(&mut self, value: B, _: Option<&B>) -> B {
+        (self.0)(value)
+    }
+}
+
+/// Jitter that might not be there, such as one picked from a config; `None` leaves every value
+/// alone.
+impl<B, J> Jitter<B> for Option<J>
+    where J: Jitter<B>
+{
+    fn jitter(&mut self, value: B, previous: Option<&B>) -> B {
+        match self {
+            Some(jitter) => jitter.jitter(value, previous),
+            None => value,
+        }
+    }
+}
+
+/// Replaces each value `v` with a uniformly random value in `[0, v]`.
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug)]
+pub struct FullJitter<R = OsRng> {
+    rng: R,
+}
+
+#[cfg(feature = "rand")]
+impl<B, R> Jitter<B> for FullJitter<R>
+    where B: SampleUniform + PartialOrd + Default,
+          R: Rng
+{
+    fn jitter(&mut self, value: B, _: Option<&B>) -> B {
+        let zero = B::default();
+        if value <= zero {
+            return value;
+        }
+        self.rng.gen_range(zero..=value)
+    }
+}
+
+/// Replaces each value `v` with a uniformly random value in `[v/2, v]`.
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug)]
+pub struct EqualJitter<R = OsRng> {
+    rng: R,
+}
+
+#[cfg(feature = "rand")]
+impl<B, R> Jitter<B> for EqualJitter<R>
+    where B: SampleUniform + PartialOrd + Default + Halve,
+          R: Rng
+{
+    fn jitter(&mut self, value: B, _: Option<&B>) -> B {
+        if value <= B::default() {
+            return value;
+        }
+        let half = value.halve();
+        self.rng.gen_range(half..=value)
+    }
+}
+
+/// Takes a random amount of up to `max_reduction` off each value, but never more than the value
+/// itself, so that it's never increased and never goes below zero.
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug)]
+pub struct DownJitter<B, R = OsRng> {
+    max_reduction: B,
+    rng: R,
+}
+
+#[cfg(feature = "rand")]
+impl<B, R> Jitter<B> for DownJitter<B, R>
+    where B: SampleUniform + PartialOrd + Default + Clone + Sub<Output = B>,
+          R: Rng
+{
+    fn jitter(&mut self, value: B, _: Option<&B>) -> B {
+        let zero = B::default();
+        if value <= zero || self.max_reduction <= zero {
+            return value;
+        }
+        let most = if self.max_reduction < value {
+            self.max_reduction.clone()
+        } else {
+            value.clone()
+        };
+        let reduction = self.rng.gen_range(zero..=most);
+        value - reduction
+    }
+}
+
+/// The "decorrelated jitter" strategy, which ignores the calculated value and instead picks a
+/// random value between `base` and three times the previous one, starting at `base`.
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug)]
+pub struct DecorrelatedJitter<B, R = OsRng> {
+    base: B,
+    rng: R,
+}
+
+#[cfg(feature = "rand")]
+impl<B, R> Jitter<B> for DecorrelatedJitter<B, R>
+    where B: SampleUniform + PartialOrd + SaturatingMul + Clone,
+          R: Rng
+{
+    fn jitter(&mut self, _: B, previous: Option<&B>) -> B {
+        let high = match previous {
+            Some(prev) => prev.saturating_mul(3),
+            None => return self.base.clone(),
+        };
+        if high <= self.base {
+            return self.base.clone();
+        }
+        self.rng.gen_range(self.base.clone()..=high)
+    }
+}
+
+/// Multiplies each value by a random factor within `1.0 ± p`.
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug)]
+pub struct FactorJitter<R = OsRng> {
+    p: f64,
+    rng: R,
+}
+
+#[cfg(feature = "rand")]
+impl<B, R> Jitter<B> for FactorJitter<R>
+    where B: Scale,
+          R: Rng
+{
+    fn jitter(&mut self, value: B, _: Option<&B>) -> B {
+        let low = (1.0 - self.p).max(0.0);
+        value.scale(self.rng.gen_range(low..=1.0 + self.p))
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: SampleUniform + PartialOrd + Default + Clone,
+          J: Jitter<B> + Clone
+{
+    /// Randomizes each value to somewhere between zero and itself, as in the "full jitter" of
+    /// https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/.  The `max`
+    /// clamp is applied before the jitter, so the result never exceeds it.
+    pub fn full_jitter(&self) -> BackoffSequence<'a, F, B, FullJitter> {
+        self.with_jitter(FullJitter { rng: OsRng })
+    }
+
+    /// `full_jitter`, drawing from an RNG seeded with `seed`, so that every iterator produces
+    /// the same values.
+    pub fn full_jitter_seeded(&self, seed: u64) -> BackoffSequence<'a, F, B, FullJitter<StdRng>> {
+        self.with_jitter(FullJitter { rng: StdRng::seed_from_u64(seed) })
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: SampleUniform + PartialOrd + Default + Halve + Clone,
+          J: Jitter<B> + Clone
+{
+    /// Randomizes each value to somewhere between half of itself and itself, the "equal
+    /// jitter" strategy.  This keeps some of the backoff while still spreading retries out.
+    pub fn equal_jitter(&self) -> BackoffSequence<'a, F, B, EqualJitter> {
+        self.with_jitter(EqualJitter { rng: OsRng })
+    }
+
+    /// `equal_jitter`, drawing from an RNG seeded with `seed`, so that every iterator produces
+    /// the same values.
+    pub fn equal_jitter_seeded(&self, seed: u64)
+                               -> BackoffSequence<'a, F, B, EqualJitter<StdRng>> {
+        self.with_jitter(EqualJitter { rng: StdRng::seed_from_u64(seed) })
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: SampleUniform + PartialOrd + Default + Clone + Sub<Output = B>,
+          J: Jitter<B> + Clone
+{
+    /// Takes a random amount of up to `max_reduction` off each value, for spreading retries out
+    /// without ever waiting longer than the `max`.  A value is never reduced below zero, and the
+    /// result is still raised to `min` if that's set.
+    pub fn jitter_down(&self, max_reduction: B) -> BackoffSequence<'a, F, B, DownJitter<B>> {
+        self.with_jitter(DownJitter {
+            max_reduction,
+            rng: OsRng,
+        })
+    }
+
+    /// `jitter_down`, drawing from an RNG seeded with `seed`, so that every iterator produces
+    /// the same values.
+    pub fn jitter_down_seeded(&self,
+                              max_reduction: B,
+                              seed: u64)
+                              -> BackoffSequence<'a, F, B, DownJitter<B, StdRng>> {
+        self.with_jitter(DownJitter {
+            max_reduction,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Scale + Clone,
+          J: Jitter<B> + Clone
+{
+    /// Spreads each value out by up to `p` in either direction, e.g. `0.2` multiplies each
+    /// value by something between `0.8` and `1.2`.  A negative, NaN or infinite `p` is treated
+    /// as zero, and the result is still clamped to `max`.
+    pub fn jitter_factor(&self, p: f64) -> BackoffSequence<'a, F, B, FactorJitter> {
+        self.with_jitter(FactorJitter {
+            p: if p > 0.0 && p.is_finite() { p } else { 0.0 },
+            rng: OsRng,
+        })
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: SampleUniform + PartialOrd + SaturatingMul + Clone,
+          J: Jitter<B> + Clone
+{
+    /// Replaces the calculated values with AWS's "decorrelated jitter": the first value is
+    /// `base`, and each one after that is random between `base` and three times the one before,
+    /// clamped to `max`.  The calculator still decides when `min` has been reached.
+    pub fn decorrelated_jitter(&self, base: B)
+                               -> BackoffSequence<'a, F, B, DecorrelatedJitter<B>> {
+        self.with_jitter(DecorrelatedJitter { base, rng: OsRng })
+    }
+
+    /// `decorrelated_jitter`, drawing from an RNG seeded with `seed`, so that every iterator
+    /// produces the same values.
+    pub fn decorrelated_jitter_seeded(&self,
+                                      base: B,
+                                      seed: u64)
+                                      -> BackoffSequence<'a, F, B, DecorrelatedJitter<B, StdRng>> {
+        self.with_jitter(DecorrelatedJitter {
+            base,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn full_jitter_within_bounds() {
+        let f = &|x| 2u64.pow(x as u32);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(1000).max_iterations(20);
+
+        let raw = backoff.iter().collect::<Vec<_>>();
+        for _ in 0..10 {
+            let jittered = backoff.full_jitter().iter().collect::<Vec<_>>();
+            assert_eq!(jittered.len(), raw.len());
+            for (j, r) in jittered.iter().zip(raw.iter()) {
+                assert!(j <= r);
+            }
+        }
+    }
+
+    #[test]
+    fn equal_jitter_within_bounds() {
+        let f = &|x| 3u64.pow(x as u32);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(5000).max_iterations(12);
+
+        let raw = backoff.iter().collect::<Vec<_>>();
+        for _ in 0..10 {
+            let jittered = backoff.equal_jitter().iter().collect::<Vec<_>>();
+            for (j, r) in jittered.iter().zip(raw.iter()) {
+                assert!(*j >= r / 2 && j <= r);
+            }
+        }
+
+        let f = &|x| Duration::from_millis(10 * x);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(10);
+        for (j, r) in backoff.equal_jitter().iter().zip(backoff.iter()) {
+            assert!(j >= r / 2 && j <= r);
+        }
+    }
+
+    #[test]
+    fn decorrelated_jitter_starts_at_base() {
+        let f = &|_| Duration::from_secs(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(Duration::from_millis(500)).max_iterations(50);
+
+        let v = backoff.decorrelated_jitter(Duration::from_millis(10)).iter().collect::<Vec<_>>();
+        assert_eq!(v[0], Duration::from_millis(10));
+        for pair in v.windows(2) {
+            assert!(pair[1] >= Duration::from_millis(10));
+            assert!(pair[1] <= pair[0] * 3);
+            assert!(pair[1] <= Duration::from_millis(500));
+        }
+    }
+
+    #[test]
+    fn decorrelated_jitter_seeded_repeats() {
+        let f = &|_| 0u64;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(10_000).max_iterations(20);
+
+        let jittered = backoff.decorrelated_jitter_seeded(5, 42);
+        let a = jittered.iter().collect::<Vec<_>>();
+        let b = jittered.iter().collect::<Vec<_>>();
+        assert_eq!(a, b);
+        assert_eq!(a, backoff.decorrelated_jitter_seeded(5, 42).iter().collect::<Vec<_>>());
+        assert_eq!(a[0], 5);
+    }
+
+    #[test]
+    fn seeded_jitter_repeats() {
+        let f = &|x| 2u64.pow(x as u32);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(100_000).max_iterations(30);
+
+        let full = backoff.full_jitter_seeded(7);
+        assert_eq!(full.iter().collect::<Vec<_>>(), full.iter().collect::<Vec<_>>());
+        assert_eq!(full.iter().collect::<Vec<_>>(),
+                   backoff.full_jitter_seeded(7).iter().collect::<Vec<_>>());
+        assert_ne!(full.iter().collect::<Vec<_>>(),
+                   backoff.full_jitter_seeded(8).iter().collect::<Vec<_>>());
+
+        let equal = backoff.equal_jitter_seeded(7);
+        assert_eq!(equal.iter().collect::<Vec<_>>(),
+                   backoff.equal_jitter_seeded(7).iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn jitter_factor_within_bounds() {
+        let f = &|x| 1000 * x;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(10);
+
+        for _ in 0..10 {
+            for (j, r) in backoff.jitter_factor(0.2).iter().zip(backoff.iter()) {
+                assert!(j >= r * 8 / 10 && j <= r * 12 / 10);
+            }
+        }
+
+        let f = &|_| Duration::from_millis(100);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(Duration::from_millis(110)).max_iterations(20);
+        for d in &backoff.jitter_factor(0.5) {
+            assert!(d >= Duration::from_millis(50) && d <= Duration::from_millis(110));
+        }
+
+        assert_eq!(backoff.jitter_factor(-1.0).iter().collect::<Vec<_>>(),
+                   backoff.iter().collect::<Vec<_>>());
+        for p in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(backoff.jitter_factor(p).iter().collect::<Vec<_>>(),
+                       backoff.iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn jitter_down_never_increases() {
+        let f = &|x| 100 * x;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(500).max_iterations(10);
+
+        for _ in 0..10 {
+            for (j, r) in backoff.jitter_down(150).iter().zip(backoff.iter()) {
+                assert!(j <= r && j + 150 >= r);
+            }
+        }
+
+        // more than the value itself stops at zero, or at `min`
+        let f = &|_| Duration::from_millis(10);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(50);
+        let jittered = backoff.jitter_down_seeded(Duration::from_secs(1), 3);
+        let v = jittered.iter().collect::<Vec<_>>();
+        assert!(v.iter().all(|d| *d <= Duration::from_millis(10)));
+        assert_eq!(v, jittered.iter().collect::<Vec<_>>());
+
+        backoff.min_floor(Duration::from_millis(4));
+        for d in &backoff.jitter_down(Duration::from_secs(1)) {
+            assert!(d >= Duration::from_millis(4) && d <= Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn full_jitter_durations() {
+        let f = &|x| Duration::from_millis(10 * x);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(Duration::from_millis(50)).max_iterations(10);
+
+        for d in &backoff.full_jitter() {
+            assert!(d <= Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn peek_matches_jittered_next() {
+        let f = &|x| 2u64.pow(x as u32);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(1000).max_iterations(20);
+
+        let jittered = backoff.full_jitter();
+        let mut iter = jittered.iter();
+        while let Some(&peeked) = iter.peek() {
+            assert_eq!(iter.peek(), Some(&peeked));
+            assert_eq!(iter.next(), Some(peeked));
+        }
+    }
+}
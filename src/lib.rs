@@ -1,19 +1,331 @@
-use std::fmt;
-use std::fmt::Debug;
+// Without the default `std` feature only the sequences and their iterators are left, which
+// need nothing more than `core` and `alloc`.  Everything that sleeps or tells the time is gone.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-#[derive(Clone)]
-pub struct BackoffSequence<'a, F: 'a, B> {
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::convert::TryFrom;
+use core::fmt;
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::mem;
+use core::ops::{Bound, Deref, RangeBounds};
+use core::time::Duration;
+
+mod adaptors;
+mod builder;
+#[cfg(feature = "chrono")]
+mod chrono;
+mod config;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "std")]
+mod deadline;
+mod error;
+#[cfg(feature = "std")]
+mod events;
+mod jitter;
+mod macros;
+mod map;
+mod ops;
+mod owned;
+mod precomputed;
+mod presets;
+mod raw;
+#[cfg(feature = "std")]
+mod retry;
+#[cfg(feature = "std")]
+mod sleep;
+#[cfg(feature = "stream")]
+mod stream;
+#[cfg(feature = "time")]
+mod time;
+#[cfg(any(feature = "tokio", feature = "async-std", feature = "wasm"))]
+mod retry_async;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(feature = "async-std")]
+pub mod async_std;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use adaptors::{BackoffIterator, ClampMax, ClampMin, Limit};
+pub use builder::BackoffSequenceBuilder;
+#[cfg(feature = "std")]
+pub use clock::{Clock, MockClock, SystemClock};
+pub use config::{BackoffConfig, ConfigJitter, Limits, Strategy};
+#[cfg(feature = "std")]
+pub use deadline::MaxElapsed;
+pub use error::BackoffError;
+#[cfg(feature = "rand")]
+pub use jitter::{DecorrelatedJitter, DownJitter, EqualJitter, FactorJitter, FullJitter};
+pub use jitter::{Jitter, JitterWith, NoJitter};
+pub use map::{Annotated, Clamped, Cumulative, MapValue, TakeWhileCumulative, Windows,
+              WithCumulative, WithIteration};
+#[cfg(feature = "std")]
+pub use owned::FnMutCalculator;
+pub use owned::{CheckedCalculator, OwnedBackoffSequence};
+pub use ops::{Bounded, CheckedAdd, Halve, Predecessor, SaturatingAdd, SaturatingMul, Scale,
+              Successor};
+pub use presets::{Constant, Exponential, ExponentialBase, Fibonacci, Geometric, Linear,
+                  Polynomial};
+pub use precomputed::Precomputed;
+pub use raw::RawIter;
+#[cfg(feature = "std")]
+pub use retry::{OnGiveUp, RetryDriver, RetryEvent, RetryOutcome};
+#[cfg(feature = "std")]
+use clock::SharedClock;
+#[cfg(feature = "std")]
+use retry::RetryHook;
+#[cfg(feature = "std")]
+pub use sleep::BlockingSleepIter;
+#[cfg(feature = "stream")]
+pub use stream::DelayStream;
+
+/// Anything that can work out the backoff value for a given iteration, which starts at 1.  This
+/// is implemented for all `Fn(u64) -> B` closures, as well as the preset calculators.
+pub trait Calculator<B> {
+    fn calculate(&self, iteration: u64) -> B;
+
+    /// Like `calculate`, but `None` ends the sequence.  By default every iteration has a value.
+    fn try_calculate(&self, iteration: u64) -> Option<B> {
+        Some(self.calculate(iteration))
+    }
+
+    /// Whether `try_calculate` can ever return `None`, so that the iterator doesn't promise an
+    /// exact length it might not reach.
+    fn can_end(&self) -> bool {
+        false
+    }
+
+    /// The name of the preset's shape, like `"exponential"`, which `Display` starts with.
+    /// Closures don't have one.
+    fn name(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl<F, B> Calculator<B> for F
+    where F: Fn(u64) -> B
+{
+    fn calculate(&self, iteration: u64) -> B {
+        self(iteration)
+    }
+}
+
+// closures are borrowed from the caller, but the presets have nobody to borrow from, so they're
+// shared instead, which keeps the sequence cheap to copy either way
+enum CalculatorHandle<'a, F: 'a> {
+    Borrowed(&'a F),
+    Shared(Arc<F>),
+}
+
+impl<'a, F> Clone for CalculatorHandle<'a, F> {
+    fn clone(&self) -> Self {
+        match *self {
+            CalculatorHandle::Borrowed(f) => CalculatorHandle::Borrowed(f),
+            CalculatorHandle::Shared(ref f) => CalculatorHandle::Shared(f.clone()),
+        }
+    }
+}
+
+impl<'a, F> Deref for CalculatorHandle<'a, F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        match *self {
+            CalculatorHandle::Borrowed(f) => f,
+            CalculatorHandle::Shared(ref f) => f,
+        }
+    }
+}
+
+// a cap on the sum of the yielded values.  The sequence is generic over B, so the arithmetic is
+// handed over by whichever method set the budget up
+#[derive(Clone, Copy)]
+struct Budget<B> {
+    remaining: B,
+    spend: fn(&mut B, B) -> B,
+    is_spent: fn(&B) -> bool,
+}
+
+impl Budget<Duration> {
+    fn duration(total: Duration) -> Self {
+        Budget {
+            remaining: total,
+            spend: |remaining, value| {
+                let value = if value > *remaining { *remaining } else { value };
+                *remaining -= value;
+                value
+            },
+            is_spent: |remaining| *remaining == Duration::from_secs(0),
+        }
+    }
+}
+
+// how far past the first iteration the min skip looks, unless told otherwise
+const DEFAULT_MIN_SEARCH_LIMIT: u64 = 10_000;
+
+// the cap set by `max_with`, from the iteration it's for.  It's `'static`, so that it doesn't
+// keep the sequence borrowed for as long as an iterator's around.
+type MaxFn<B> = Arc<dyn Fn(u64) -> B + Send + Sync>;
+
+// the predicate set by `until`, and whether the value that satisfies it is yielded too
+type Until<B> = (Arc<dyn Fn(&B) -> bool + Send + Sync>, bool);
+
+pub struct BackoffSequence<'a, F: 'a, B, J = NoJitter> {
     max_iterations: Option<u64>,
     min_value: Option<B>,
+    // false when `min` is only a floor, set by `min_floor`
+    min_skip: bool,
     max_value: Option<B>,
-    calculator: &'a F,
+    // used instead of `max_value` when set, by `max_with`
+    max_fn: Option<MaxFn<B>>,
+    min_search_limit: u64,
+    // the value yielded before the calculator's first, set by `immediate_first`
+    first_value: Option<B>,
+    descending: bool,
+    // iterations passed over without being calculated, set by `skip_first`
+    skip: u64,
+    calculator: CalculatorHandle<'a, F>,
+    budget: Option<Budget<B>>,
+    until: Option<Until<B>>,
+    overflow_value: Option<fn() -> B>,
+    #[cfg(feature = "std")]
+    on_retry: Option<RetryHook<B>>,
+    #[cfg(feature = "std")]
+    clock: Option<SharedClock>,
+    jitter: J,
+}
+
+// the calculator's only ever borrowed or shared, so it doesn't need to be Clone itself
+impl<'a, F, B, J> Clone for BackoffSequence<'a, F, B, J>
+    where B: Clone,
+          J: Clone
+{
+    fn clone(&self) -> Self {
+        BackoffSequence {
+            max_iterations: self.max_iterations,
+            min_value: self.min_value.clone(),
+            min_skip: self.min_skip,
+            max_value: self.max_value.clone(),
+            max_fn: self.max_fn.clone(),
+            min_search_limit: self.min_search_limit,
+            first_value: self.first_value.clone(),
+            descending: self.descending,
+            skip: self.skip,
+            calculator: self.calculator.clone(),
+            budget: self.budget.clone(),
+            until: self.until.clone(),
+            overflow_value: self.overflow_value,
+            #[cfg(feature = "std")]
+            on_retry: self.on_retry.clone(),
+            #[cfg(feature = "std")]
+            clock: self.clock.clone(),
+            jitter: self.jitter.clone(),
+        }
+    }
 }
 
-impl<'a, F, B> Debug for BackoffSequence<'a, F, B>
+impl<'a, F, B, J> Debug for BackoffSequence<'a, F, B, J>
     where B: Debug
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "mi: {:?}, mv: {:?}", self.max_iterations, self.max_value)
+        f.debug_struct("BackoffSequence")
+            .field("max_iterations", &self.max_iterations)
+            .field("min_value", &self.min_value)
+            .field("max_value", &self.max_value)
+            .finish()
+    }
+}
+
+/// A summary of the policy for logging, like `exponential, max 5 attempts, clamp [100ms, 30s]`.
+/// Values are shown with `Debug`, which is what `Duration` has.  A closure can't say what shape
+/// it is, so for those only the limits are shown.
+impl<'a, F, B, J> fmt::Display for BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut empty = true;
+        let mut part = |f: &mut fmt::Formatter, part: fmt::Arguments| {
+            let sep = if empty { "" } else { ", " };
+            empty = false;
+            write!(f, "{}{}", sep, part)
+        };
+
+        if let Some(name) = self.calculator.name() {
+            part(f, format_args!("{}", name))?;
+        }
+        if let Some(n) = self.max_iterations {
+            part(f, format_args!("max {} attempt{}", n, if n == 1 { "" } else { "s" }))?;
+        }
+        match (&self.min_value, &self.max_value) {
+            (Some(min), Some(max)) if self.min_skip => {
+                part(f, format_args!("clamp [{:?}, {:?}]", min, max))?
+            }
+            (Some(min), max) => {
+                let kind = if self.min_skip { "min" } else { "floor" };
+                part(f, format_args!("{} {:?}", kind, min))?;
+                if let Some(max) = max {
+                    part(f, format_args!("max {:?}", max))?;
+                }
+            }
+            (None, Some(max)) => part(f, format_args!("max {:?}", max))?,
+            (None, None) => (),
+        }
+        if self.max_fn.is_some() {
+            part(f, format_args!("max per attempt"))?;
+        }
+        if self.first_value.is_some() {
+            part(f, format_args!("immediate first"))?;
+        }
+        if self.skip > 0 {
+            part(f, format_args!("skip first {}", self.skip))?;
+        }
+        if self.descending {
+            part(f, format_args!("descending"))?;
+        }
+        if let Some(ref budget) = self.budget {
+            part(f, format_args!("total {:?}", budget.remaining))?;
+        }
+        match self.until {
+            Some((_, true)) => part(f, format_args!("until inclusive"))?,
+            Some((_, false)) => part(f, format_args!("until"))?,
+            None => (),
+        }
+        if self.overflow_value.is_some() {
+            part(f, format_args!("saturating"))?;
+        }
+
+        if empty {
+            f.write_str("unbounded")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares everything that's been configured apart from closures, which can't be compared:
+/// the calculator, the jitter, `max_with`, `until`, and the `on_retry` hook and clock.  So
+/// sequences built from different closures are equal as long as they're otherwise configured the
+/// same way.  `saturating` only counts as whether it's been called.
+impl<'a, 'b, F, G, B, J, K> PartialEq<BackoffSequence<'b, G, B, K>>
+    for BackoffSequence<'a, F, B, J>
+    where B: PartialEq
+{
+    fn eq(&self, other: &BackoffSequence<'b, G, B, K>) -> bool {
+        self.max_iterations == other.max_iterations && self.min_value == other.min_value &&
+        self.min_skip == other.min_skip && self.max_value == other.max_value &&
+        self.min_search_limit == other.min_search_limit &&
+        self.first_value == other.first_value && self.descending == other.descending &&
+        self.skip == other.skip &&
+        self.budget.as_ref().map(|b| &b.remaining) ==
+        other.budget.as_ref().map(|b| &b.remaining) &&
+        self.overflow_value.is_some() == other.overflow_value.is_some()
     }
 }
 
@@ -22,183 +334,999 @@ impl<'a, F, B> BackoffSequence<'a, F, B>
           B: PartialOrd + Clone
 {
     pub fn new(f: &'a F) -> Self {
+        BackoffSequence::with_calculator(CalculatorHandle::Borrowed(f))
+    }
+}
+
+impl<B> BackoffSequence<'static, Box<dyn Fn(u64) -> B>, B>
+    where B: PartialOrd + Clone
+{
+    /// A sequence using a calculator picked at runtime.  These all have the same type whatever
+    /// the closure is, so they can be kept together in a `Vec` or a map.
+    pub fn from_boxed(f: Box<dyn Fn(u64) -> B>) -> Self {
+        BackoffSequence::shared(f)
+    }
+}
+
+impl<'a, F, B> BackoffSequence<'a, F, B>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone
+{
+    fn with_calculator(calculator: CalculatorHandle<'a, F>) -> Self {
         BackoffSequence {
-            calculator: f,
+            calculator,
             max_iterations: None,
             min_value: None,
+            min_skip: true,
             max_value: None,
+            max_fn: None,
+            min_search_limit: DEFAULT_MIN_SEARCH_LIMIT,
+            first_value: None,
+            descending: false,
+            skip: 0,
+            budget: None,
+            until: None,
+            overflow_value: None,
+            #[cfg(feature = "std")]
+            on_retry: None,
+            #[cfg(feature = "std")]
+            clock: None,
+            jitter: NoJitter,
         }
     }
 
+    // for the presets, which own their calculators
+    fn shared(f: F) -> Self {
+        BackoffSequence::with_calculator(CalculatorHandle::Shared(Arc::new(f)))
+    }
+}
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
+{
     pub fn max_iterations(&mut self, x: u64) -> &mut Self {
         self.max_iterations = Some(x);
         self
     }
 
+    /// Skips ahead to the first iteration whose value is at least `x`, then raises any later
+    /// values that drop below `x` back up to it.  The skipped iterations don't count towards
+    /// `max_iterations`.  See `min_floor` for a min that only raises values.
     pub fn min(&mut self, x: B) -> &mut Self {
         self.min_value = Some(x);
+        self.min_skip = true;
+        self
+    }
+
+    /// Raises any value below `x` up to it, the way `max` lowers values above it.  Unlike `min`
+    /// nothing is skipped, so the sequence starts from the first iteration, at `x` if that's
+    /// below it.  This replaces any `min` set before, and vice versa.
+    pub fn min_floor(&mut self, x: B) -> &mut Self {
+        self.min_value = Some(x);
+        self.min_skip = false;
         self
     }
 
+    /// Caps every value at `x`.  If this is less than `min`, the max wins, and `min` is treated
+    /// as though it were `x`.  Values that can't be compared at all, like a float's NaN, are
+    /// replaced with `x` too; without a max they're passed through as they are.
     pub fn max(&mut self, x: B) -> &mut Self {
         self.max_value = Some(x);
+        self.max_fn = None;
+        self
+    }
+
+    /// Caps the value for each iteration `n` at `f(n)`, for a cap that tightens or loosens as
+    /// the attempts go on.  Otherwise it's like `max`, and replaces any `max` set before, and
+    /// vice versa.  The sequence can't settle at a cap that might change, so the calculator
+    /// keeps being called and needs to saturate rather than overflow, as the presets do.  A
+    /// `min` above the cap isn't lowered to it for the skip, as it is with `max`.  Like the
+    /// calculator of an `OwnedBackoffSequence`, `f` can't borrow anything.
+    pub fn max_with<G>(&mut self, f: G) -> &mut Self
+        where G: Fn(u64) -> B + Send + Sync + 'static
+    {
+        self.max_value = None;
+        self.max_fn = Some(Arc::new(f));
+        self
+    }
+
+    /// Ends the sequence at the first value for which `predicate` returns true, without
+    /// yielding it, like `take_while` with the condition turned around.  It sees each value
+    /// after the clamping and jitter, so `until(|d| *d > ten_secs)` stops at the first wait that
+    /// would be longer than ten seconds.  Like `max_with`, `predicate` can't borrow anything.
+    pub fn until<P>(&mut self, predicate: P) -> &mut Self
+        where P: Fn(&B) -> bool + Send + Sync + 'static
+    {
+        self.until = Some((Arc::new(predicate), false));
+        self
+    }
+
+    /// Like `until`, but the value that satisfies `predicate` is yielded as the last one.
+    pub fn until_inclusive<P>(&mut self, predicate: P) -> &mut Self
+        where P: Fn(&B) -> bool + Send + Sync + 'static
+    {
+        self.until = Some((Arc::new(predicate), true));
+        self
+    }
+
+    /// Starts the sequence at iteration `n + 1`, without calculating the `n` before it.  This
+    /// gives the same values as `iter().skip(n)` (the skip up to `min` starts from iteration
+    /// `n + 1` too), and as with that the skipped iterations count towards `max_iterations`.
+    pub fn skip_first(&mut self, n: u64) -> &mut Self {
+        self.skip = n;
+        self
+    }
+
+    /// For calculators whose values shrink rather than grow.  The `min` becomes a floor that
+    /// nothing is skipped for, and it's the `min` that the sequence settles at: once a value
+    /// reaches it the calculator isn't called again.  The `max` is still a cap, but values
+    /// can come back down from it.
+    pub fn descending(&mut self) -> &mut Self {
+        self.descending = true;
         self
     }
 
-    pub fn iter(&self) -> BackoffSequenceIterator<F, B> {
+    /// How many iterations past the first the skip up to `min` will try before giving up, 10,000
+    /// by default.  If the calculator hasn't reached `min` by then, nothing is skipped, and the
+    /// sequence starts from the first iteration with `min` as a floor.
+    pub fn min_search_limit(&mut self, steps: u64) -> &mut Self {
+        self.min_search_limit = steps;
+        self
+    }
+
+    pub fn iter(&self) -> BackoffSequenceIterator<'_, F, B, J> {
+        self.iter_with(CalculatorHandle::Borrowed(&self.calculator))
+    }
+
+    /// Turns the sequence into an iterator that holds on to the calculator itself, rather than
+    /// borrowing it from the sequence.  For the presets, and sequences made with `builder()`,
+    /// the iterator is `'static`, so it can be returned from a function.
+    pub fn into_owned_iter(self) -> BackoffSequenceIterator<'a, F, B, J> {
+        let calculator = self.calculator.clone();
+        self.iter_with(calculator)
+    }
+
+    fn iter_with<'b>(&self, calculator: CalculatorHandle<'b, F>)
+                     -> BackoffSequenceIterator<'b, F, B, J> {
         BackoffSequenceIterator {
-            iteration: 0,
+            iteration: self.skip,
             max_iterations: self.max_iterations,
-            calculator: self.calculator,
+            calculator,
             current_value: None,
             max_value: self.max_value.clone(),
+            max_fn: self.max_fn.clone(),
+            min_value: self.effective_min(),
+            min_search_limit: self.min_search_limit,
+            first_value: self.first_value.clone(),
+            descending: self.descending,
+            min_skipped: !self.min_skip || self.descending,
+            at_bound: false,
+            clamped: ClampedTo::Neither,
+            budget: self.budget.clone(),
+            until: self.until.clone(),
+            overflow_value: self.overflow_value,
+            overflowed: None,
+            jitter: self.jitter.clone(),
+            start: Start {
+                iteration: self.skip,
+                max_iterations: self.max_iterations,
+                min_skipped: !self.min_skip || self.descending,
+                first_value: self.first_value.clone(),
+                budget: self.budget.clone(),
+            },
+            peeked: None,
+        }
+    }
+
+    /// Runs every value through `f` after it's been calculated and clamped.  The result is
+    /// clamped to `max` again afterwards.
+    pub fn jitter_with<G>(&self, f: G) -> BackoffSequence<'a, F, B, JitterWith<G>>
+        where G: Fn(B) -> B
+    {
+        self.with_jitter(JitterWith(f))
+    }
+
+    /// Checks for settings that are allowed, but probably not what was meant: a `min` above the
+    /// `max`, a `max_iterations` of 0, or a calculator whose first value can't be compared, like
+    /// NaN.  This calls the calculator once.
+    pub fn validate(&self) -> Result<(), BackoffError> {
+        if let (Some(min), Some(max)) = (&self.min_value, &self.max_value) {
+            if min > max {
+                return Err(BackoffError::MinAboveMax);
+            }
+        }
+
+        if self.max_iterations == Some(0) {
+            return Err(BackoffError::NoIterations);
+        }
+
+        match self.calculator.try_calculate(1) {
+            Some(ref first) if unordered(first) => Err(BackoffError::UnorderedValue),
+            _ => Ok(()),
+        }
+    }
+
+    // a min above the max gets lowered to it, so that the skip stops as soon as it reaches the
+    // max instead of carrying on past it
+    fn effective_min(&self) -> Option<B> {
+        match (&self.min_value, &self.max_value) {
+            (Some(min), Some(max)) if min > max => Some(max.clone()),
+            (min, _) => min.clone(),
+        }
+    }
+
+    // a copy of this sequence with a different jitter stage
+    fn with_jitter<K>(&self, jitter: K) -> BackoffSequence<'a, F, B, K> {
+        BackoffSequence {
+            max_iterations: self.max_iterations,
             min_value: self.min_value.clone(),
+            min_skip: self.min_skip,
+            max_value: self.max_value.clone(),
+            max_fn: self.max_fn.clone(),
+            min_search_limit: self.min_search_limit,
+            first_value: self.first_value.clone(),
+            descending: self.descending,
+            skip: self.skip,
+            calculator: self.calculator.clone(),
+            budget: self.budget.clone(),
+            until: self.until.clone(),
+            overflow_value: self.overflow_value,
+            #[cfg(feature = "std")]
+            on_retry: self.on_retry.clone(),
+            #[cfg(feature = "std")]
+            clock: self.clock.clone(),
+            jitter,
         }
     }
 }
 
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone + Default,
+          J: Jitter<B> + Clone
+{
+    /// Yields zero (`B::default()`) before the calculator's first value, for a first retry
+    /// with no wait at all.  It counts towards `max_iterations`, and isn't clamped or jittered.
+    /// The calculator still starts from iteration 1 for the value after it, and `iteration()`
+    /// reports 0 for the immediate value.  The retry helpers already make the first attempt
+    /// without waiting, so with them this means a second attempt straight after the first.
+    pub fn immediate_first(&mut self) -> &mut Self {
+        self.first_value = Some(B::default());
+        self
+    }
+}
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone + Predecessor + Successor,
+          J: Jitter<B> + Clone
+{
+    /// Sets both `min` and `max` from a range, so `clamp(a..=b)` is `min(a).max(b)`.  An
+    /// excluded end is moved to the nearest value inside the range, so `clamp(a..b)` has a max
+    /// of `b.predecessor()`, and an unbounded end clears that bound.
+    pub fn clamp<R>(&mut self, range: R) -> &mut Self
+        where R: RangeBounds<B>
+    {
+        self.min_value = match range.start_bound() {
+            Bound::Included(x) => Some(x.clone()),
+            Bound::Excluded(x) => Some(x.successor()),
+            Bound::Unbounded => None,
+        };
+        self.min_skip = true;
+        self.max_value = match range.end_bound() {
+            Bound::Included(x) => Some(x.clone()),
+            Bound::Excluded(x) => Some(x.predecessor()),
+            Bound::Unbounded => None,
+        };
+        self.max_fn = None;
+        self
+    }
+}
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone + Bounded,
+          J: Jitter<B> + Clone
+{
+    /// Treats the calculator running out, like a `checked_*` overflow in `from_checked`, as the
+    /// values having grown too big rather than as the end of the sequence: from then on the
+    /// sequence stays at `max`, or the largest value `B` can hold.  A plain closure has no way
+    /// to report overflow, so this does nothing for one; only `from_checked` calculators and the
+    /// presets, which saturate anyway, plateau.
+    pub fn saturating(&mut self) -> &mut Self {
+        self.overflow_value = Some(B::max_value);
+        self
+    }
+}
+
+impl<'a, F, J> BackoffSequence<'a, F, Duration, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    /// Ends the sequence once the yielded values add up to `budget`.  The last value is cut short
+    /// so that the total comes out at exactly `budget`.
+    pub fn total_duration(&mut self, budget: Duration) -> &mut Self {
+        self.budget = Some(Budget::duration(budget));
+        self
+    }
+}
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone + SaturatingAdd + Default,
+          J: Jitter<B> + Clone
+{
+    /// The sum of every value in the sequence, which is `iter().sum()` but saturating rather
+    /// than overflowing, and for any `B`.  `retry` never waits for the last value, so this is a
+    /// bit more than it can spend waiting.  It goes through every value, so it only returns if
+    /// something bounds the sequence, normally `max_iterations`.
+    pub fn total(&self) -> B {
+        self.iter().fold(B::default(), |total, value| total.saturating_add(&value))
+    }
+
+    /// Like `total`, but `None` if the sum overflows.
+    pub fn checked_total(&self) -> Option<B>
+        where B: CheckedAdd
+    {
+        self.iter().try_fold(B::default(), |total, value| total.checked_add(&value))
+    }
+}
+
 // Don't impl this one, it moves the BackoffSequence
 // impl<'a, F, B> IntoIterator for BackoffSequence<'a, F: Fn(u64) -> B, B>
 
-impl<'a, F, B> IntoIterator for &'a BackoffSequence<'a, F, B>
-    where F: Fn(u64) -> B,
-          B: PartialOrd + Clone
+impl<'a, F, B, J> IntoIterator for &'a BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
 {
     type Item = B;
-    type IntoIter = BackoffSequenceIterator<'a, F, B>;
+    type IntoIter = BackoffSequenceIterator<'a, F, B, J>;
     // TODO make this able to return any of a set of iterators in this module, so that I can go for
     // a basic unbounded iterator with very little state or logic, and then adapt it with functions
-    // to do things like clamp the value or limit iterations or whatever
+    // to do things like clamp the value or limit iterations or whatever.  raw_iter and the
+    // adaptors are the pieces, but the limits here are still fields checked in next
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-pub struct BackoffSequenceIterator<'a, F: 'a, B> {
-    calculator: &'a F,
+// true for values like NaN that aren't even equal to themselves
+fn unordered<B: PartialOrd>(value: &B) -> bool {
+    value.partial_cmp(value).is_none()
+}
+
+pub struct BackoffSequenceIterator<'a, F: 'a, B, J = NoJitter> {
+    calculator: CalculatorHandle<'a, F>,
 
     iteration: u64,
     max_iterations: Option<u64>,
     current_value: Option<B>,
     min_value: Option<B>,
+    // with `max_with`, this is the cap for the current iteration
     max_value: Option<B>,
+    max_fn: Option<MaxFn<B>>,
+    min_search_limit: u64,
+    // still to come, from `immediate_first`
+    first_value: Option<B>,
+    descending: bool,
+    min_skipped: bool,
+    at_bound: bool,
+    clamped: ClampedTo,
+    budget: Option<Budget<B>>,
+    until: Option<Until<B>>,
+    overflow_value: Option<fn() -> B>,
+    // once the calculator's overflowed, the value that's used in its place
+    overflowed: Option<B>,
+    jitter: J,
+
+    // what the fields above that change during iteration started out as, for reset()
+    start: Start<B>,
+    // a value worked out by peek(), along with the state to move on to once it's taken
+    peeked: Option<(Option<B>, Progress<B>)>,
+}
+
+#[derive(Clone)]
+struct Start<B> {
+    iteration: u64,
+    max_iterations: Option<u64>,
+    // already true when there's nothing to skip, with `min_floor`
+    min_skipped: bool,
+    first_value: Option<B>,
+    budget: Option<Budget<B>>,
+}
+
+// which limit, if any, the last value was held to, for `annotated`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ClampedTo {
+    Neither,
+    Min,
+    Max,
+}
+
+#[derive(Clone)]
+struct Progress<B> {
+    iteration: u64,
+    max_iterations: Option<u64>,
+    current_value: Option<B>,
+    first_value: Option<B>,
+    min_skipped: bool,
+    at_bound: bool,
+    clamped: ClampedTo,
+    budget: Option<Budget<B>>,
+    overflowed: Option<B>,
 }
 
-impl<'a, F, B> Debug for BackoffSequenceIterator<'a, F, B>
+/// A copy of the iterator at its current position, which then carries on independently.  Both
+/// share the same calculator.
+impl<'a, F, B, J> Clone for BackoffSequenceIterator<'a, F, B, J>
+    where B: Clone,
+          J: Clone
+{
+    fn clone(&self) -> Self {
+        BackoffSequenceIterator {
+            calculator: self.calculator.clone(),
+            iteration: self.iteration,
+            max_iterations: self.max_iterations,
+            current_value: self.current_value.clone(),
+            min_value: self.min_value.clone(),
+            max_value: self.max_value.clone(),
+            max_fn: self.max_fn.clone(),
+            min_search_limit: self.min_search_limit,
+            first_value: self.first_value.clone(),
+            descending: self.descending,
+            min_skipped: self.min_skipped,
+            at_bound: self.at_bound,
+            clamped: self.clamped,
+            budget: self.budget.clone(),
+            until: self.until.clone(),
+            overflow_value: self.overflow_value,
+            overflowed: self.overflowed.clone(),
+            jitter: self.jitter.clone(),
+            start: self.start.clone(),
+            peeked: self.peeked.clone(),
+        }
+    }
+}
+
+impl<'a, F, B, J> Debug for BackoffSequenceIterator<'a, F, B, J>
     where B: Debug
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f,
-               "i: {:?}, mi: {:?}, cur: {:?}, min: {:?}, max: {:?}",
-               self.iteration,
-               self.max_iterations,
-               self.current_value,
-               self.min_value,
-               self.max_value)
+        f.debug_struct("BackoffSequenceIterator")
+            .field("iteration", &self.iteration)
+            .field("max_iterations", &self.max_iterations)
+            .field("current_value", &self.current_value)
+            .field("min_value", &self.min_value)
+            .field("max_value", &self.max_value)
+            .finish()
     }
 }
 
-impl<'a, F, B> Iterator for BackoffSequenceIterator<'a, F, B>
-    where F: Fn(u64) -> B,
-          B: PartialOrd + Clone
+impl<'a, F, B, J> BackoffSequenceIterator<'a, F, B, J>
+    where B: PartialOrd + Clone,
+          J: Jitter<B>
 {
-    type Item = B;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(mi) = self.max_iterations {
-            if self.iteration >= mi {
-                return None;
+    // jitter runs on the already clamped value, and then gets clamped again so that it can't
+    // push the result past the min or max
+    fn jittered(&mut self, value: B) -> B {
+        let value = self.jitter.jitter(value, self.current_value.as_ref());
+        let value = self.floored(value);
+        match self.max_value {
+            Some(ref max) if value > *max || unordered(&value) => {
+                self.clamped = ClampedTo::Max;
+                max.clone()
             }
+            _ => value,
         }
+    }
 
-        self.iteration += 1;
-
-        // check max value prior to calculations, to avoid integer overflow
-        match (&self.current_value, &self.max_value) {
-            (&Some(ref cur), &Some(ref max)) if *cur >= *max => return self.max_value.clone(),
-            _ => (),
+    fn floored(&mut self, value: B) -> B {
+        match self.min_value {
+            Some(ref min) if value < *min => {
+                self.clamped = ClampedTo::Min;
+                min.clone()
+            }
+            _ => value,
         }
+    }
+}
 
-        let mut new_value = Some((self.calculator)(self.iteration));
+impl<'a, F, B, J> BackoffSequenceIterator<'a, F, B, J> {
+    /// The iteration that the last value came from, starting at 1, or 0 before the first value
+    /// (`n` with `skip_first(n)`).  The skip up to `min` jumps this forward along with
+    /// `max_iterations`.
+    pub fn iteration(&self) -> u64 {
+        self.iteration
+    }
 
-        // if the value is less than the minimum, advance the iterator until the value is >= the
-        // minimum, and increase the max iterations (if required) by the corresponding #
-        if self.min_value.is_some() {
-            let min = self.min_value.clone().unwrap();
-            let mut cur = new_value.clone().unwrap();
-            let mut iter = self.iteration;
+    /// How many more values there will be, if `max_iterations` is set.
+    pub fn remaining(&self) -> Option<u64> {
+        self.max_iterations.map(|mi| mi.saturating_sub(self.iteration))
+    }
 
-            while cur < min {
-                iter += 1;
-                cur = (self.calculator)(iter);
-            }
+    /// `remaining`, for retry loops that think in attempts: `None` without a `max_iterations`.
+    /// Iterations skipped to get up to `min` don't use up any attempts, so this only goes down
+    /// by one for each value, even when `iteration` jumps ahead.
+    pub fn attempts_remaining(&self) -> Option<u64> {
+        self.remaining()
+    }
 
-            if let Some(mi) = self.max_iterations {
-                self.max_iterations = Some(mi + (iter - self.iteration));
-            }
+    /// How far through the sequence this is, from 0.0 before the first value to 1.0 after the
+    /// last, or `None` without a `max_iterations`.  Like `attempts_remaining` it counts values
+    /// rather than iterations, so the skip up to `min` doesn't move it on, and neither do the
+    /// iterations skipped by `skip_first`.  An empty sequence is already done, at 1.0.
+    pub fn progress(&self) -> Option<f64> {
+        let total = self.start.max_iterations?.saturating_sub(self.start.iteration);
+        let remaining = self.remaining()?;
+        if total == 0 {
+            return Some(1.0);
+        }
+        Some(total.saturating_sub(remaining) as f64 / total as f64)
+    }
+}
 
-            self.iteration = iter;
-            new_value = Some(cur);
+impl<'a, F, B, J> BackoffSequenceIterator<'a, F, B, J>
+    where B: Clone
+{
+    /// Starts the sequence over again, as if this were a fresh iterator from the same
+    /// `BackoffSequence`, including the skip up to `min`.  Any state kept by the jitter, such as
+    /// its random number generator, carries on from where it was.
+    pub fn reset(&mut self) {
+        self.iteration = self.start.iteration;
+        self.max_iterations = self.start.max_iterations;
+        self.current_value = None;
+        self.first_value = self.start.first_value.clone();
+        self.min_skipped = self.start.min_skipped;
+        self.at_bound = false;
+        self.clamped = ClampedTo::Neither;
+        self.budget = self.start.budget.clone();
+        self.overflowed = None;
+        self.peeked = None;
+    }
 
-            self.min_value = None;
+    fn snapshot(&self) -> Progress<B> {
+        Progress {
+            iteration: self.iteration,
+            max_iterations: self.max_iterations,
+            current_value: self.current_value.clone(),
+            first_value: self.first_value.clone(),
+            min_skipped: self.min_skipped,
+            at_bound: self.at_bound,
+            clamped: self.clamped,
+            budget: self.budget.clone(),
+            overflowed: self.overflowed.clone(),
         }
+    }
 
-        self.current_value = match (&new_value, &self.max_value) {
-            //            (None, _) => return None,
-            //            (_, None) => self.current_value,
-            // (&Some(ref c), &Some(ref m)) if *c <= *m => new_value.clone(),
-            (&Some(ref new), &Some(ref max)) if *new > *max => self.max_value.clone(),
-            _ => new_value.clone(),
-        };
+    // like restore, but hands back the state that was replaced, rather than dropping it
+    fn replace_progress(&mut self, progress: Progress<B>) -> Progress<B> {
+        Progress {
+            iteration: mem::replace(&mut self.iteration, progress.iteration),
+            max_iterations: mem::replace(&mut self.max_iterations, progress.max_iterations),
+            current_value: mem::replace(&mut self.current_value, progress.current_value),
+            first_value: mem::replace(&mut self.first_value, progress.first_value),
+            min_skipped: mem::replace(&mut self.min_skipped, progress.min_skipped),
+            at_bound: mem::replace(&mut self.at_bound, progress.at_bound),
+            clamped: mem::replace(&mut self.clamped, progress.clamped),
+            budget: mem::replace(&mut self.budget, progress.budget),
+            overflowed: mem::replace(&mut self.overflowed, progress.overflowed),
+        }
+    }
 
-        self.current_value.clone()
+    fn restore(&mut self, progress: Progress<B>) {
+        self.iteration = progress.iteration;
+        self.max_iterations = progress.max_iterations;
+        self.current_value = progress.current_value;
+        self.first_value = progress.first_value;
+        self.min_skipped = progress.min_skipped;
+        self.at_bound = progress.at_bound;
+        self.clamped = progress.clamped;
+        self.budget = progress.budget;
+        self.overflowed = progress.overflowed;
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::thread::sleep;
-    use std::time::Duration;
+impl<'a, F, B, J> BackoffSequenceIterator<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    /// The value that the next call to `next()` will return, without moving the iterator on.
+    /// Peeking again before calling `next()` gives the same value, even with random jitter.
+    pub fn peek(&mut self) -> Option<&B> {
+        if self.peeked.is_none() {
+            let before = self.snapshot();
+            let value = self.next();
+            let after = self.replace_progress(before);
+            self.peeked = Some((value, after));
+        }
 
-    #[allow(dead_code)]
-    fn double(x: u64) -> u64 {
-        x * 2
+        match self.peeked {
+            Some((ref value, _)) => value.as_ref(),
+            None => None,
+        }
     }
 
-    fn base_2_exp_calculator(x: u64) -> u64 {
-        2u64.pow(x as u32) - 1
+    // the calculator's value for `iteration`, or None if the calculator says the sequence is
+    // over.  In saturating mode that None counts as overflow instead, and the overflow value is
+    // used for it and everything after it
+    fn calculate(&mut self, iteration: u64) -> Option<B> {
+        if let Some(ref value) = self.overflowed {
+            return Some(value.clone());
+        }
+
+        match (self.calculator.try_calculate(iteration), self.overflow_value) {
+            (None, Some(overflow_value)) => {
+                let value = self.max_value.clone().unwrap_or_else(overflow_value);
+                self.overflowed = Some(value.clone());
+                Some(value)
+            }
+            (value, _) => value,
+        }
     }
 
-    #[test]
-    fn new_builder_with_function_ptr() {
-        let f = &base_2_exp_calculator;
-        let mut _x = BackoffSequence::new(f);
+    // the min and max for a descending sequence, where it's reaching the min that means the
+    // calculator can stop being asked
+    fn clamped_descending(&mut self, value: B) -> B {
+        if let Some(ref min) = self.min_value {
+            if value <= *min {
+                self.at_bound = !self.calculator.can_end();
+                if value < *min {
+                    self.clamped = ClampedTo::Min;
+                }
+                return min.clone();
+            }
+        }
+        match self.max_value {
+            Some(ref max) if value > *max || unordered(&value) => {
+                self.clamped = ClampedTo::Max;
+                max.clone()
+            }
+            _ => value,
+        }
     }
 
-    #[test]
-    fn new_builder_with_closure_ptr() {
-        let f = &|x| 2u64.pow(x as u32);
-        let mut _x = BackoffSequence::new(&f);
+    // with `max_with`, works out the cap for the iteration that's about to be clamped
+    fn refresh_max(&mut self) {
+        if let Some(ref f) = self.max_fn {
+            self.max_value = Some(f(self.iteration));
+        }
     }
 
-    #[test]
-    fn new_builder_one_opt() {
-        let f = &base_2_exp_calculator;
-        let mut x = BackoffSequence::new(f);
-        x.min(5);
+    // whether something other than max_iterations can end the sequence, so that nothing can be
+    // skipped over, and the remaining count is only an upper bound
+    fn can_end(&self) -> bool {
+        self.budget.is_some() || self.until.is_some() ||
+        (self.calculator.can_end() && self.overflow_value.is_none())
     }
 
-    #[test]
-    fn new_builder_all_opt() {
-        let f = &base_2_exp_calculator;
-        let mut x = BackoffSequence::new(f);
-        x.min(5).max(1500).max_iterations(500);
+    // ends the sequence early, by using up the rest of max_iterations, so that everything that
+    // looks at the remaining count agrees that there's nothing left
+    fn finish(&mut self) -> Option<B> {
+        self.iteration -= 1;
+        self.max_iterations = Some(self.iteration);
+        None
     }
+}
 
-    #[test]
-    fn new_builder_duration() {
+impl<'a, F, B, J> Iterator for BackoffSequenceIterator<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((value, after)) = self.peeked.take() {
+            self.restore(after);
+            return value;
+        }
+
+        if let Some(mi) = self.max_iterations {
+            if self.iteration >= mi {
+                return None;
+            }
+        }
+
+        if let Some(ref budget) = self.budget {
+            if (budget.is_spent)(&budget.remaining) {
+                return None;
+            }
+        }
+
+        self.clamped = ClampedTo::Neither;
+
+        // the immediate value stands in for an iteration, rather than being one
+        if let Some(value) = self.first_value.take() {
+            self.max_iterations = self.max_iterations.map(|mi| mi - 1);
+            return Some(value);
+        }
+
+        // there's no iteration after u64::MAX, which nth or skip_first can get to without a
+        // max_iterations, so the calculator's asked about that one again
+        self.iteration = self.iteration.saturating_add(1);
+        self.refresh_max();
+
+        // once the max has been reached (or the min, when descending), stop calling the
+        // calculator, to avoid integer overflow
+        let bound = if self.descending { &self.min_value } else { &self.max_value };
+        let value = match *bound {
+            Some(ref bound) if self.at_bound => {
+                self.clamped = if self.descending { ClampedTo::Min } else { ClampedTo::Max };
+                bound.clone()
+            }
+            _ => {
+                let mut new_value = match self.calculate(self.iteration) {
+                    Some(value) => value,
+                    None => return self.finish(),
+                };
+
+                // if the value is less than the minimum, advance the iterator until the value is
+                // >= the minimum, and increase the max iterations (if required) by the
+                // corresponding #
+                if self.min_skipped {
+                    // after the skip, the min is a floor
+                    new_value = self.floored(new_value);
+                } else if let Some(min) = self.min_value.clone() {
+                    let limit = self.iteration.saturating_add(self.min_search_limit);
+                    let mut iter = self.iteration;
+                    // the value already worked out for this iteration is where the search starts,
+                    // so the calculator's only asked about the iterations after it
+                    let mut skipped_value = new_value;
+
+                    while skipped_value < min && iter < limit {
+                        iter += 1;
+                        skipped_value = match self.calculate(iter) {
+                            Some(value) => value,
+                            None => return self.finish(),
+                        };
+                    }
+
+                    if skipped_value < min {
+                        // the min's out of reach, so start here, and floor it instead
+                        self.clamped = ClampedTo::Min;
+                        new_value = min;
+                    } else {
+                        if let Some(mi) = self.max_iterations {
+                            let skipped = iter - self.iteration;
+                            self.max_iterations = Some(mi.saturating_add(skipped));
+                        }
+
+                        self.iteration = iter;
+                        self.refresh_max();
+                        new_value = skipped_value;
+                    }
+                }
+                self.min_skipped = true;
+
+                if self.descending {
+                    self.clamped_descending(new_value)
+                } else {
+                    match self.max_value {
+                        Some(ref max) if new_value >= *max || unordered(&new_value) => {
+                            // unless the calculator gets to say when it's done, which it can
+                            // only do if it keeps getting asked, or the max might move
+                            self.at_bound = !self.calculator.can_end() && self.max_fn.is_none();
+                            if new_value != *max {
+                                self.clamped = ClampedTo::Max;
+                            }
+                            max.clone()
+                        }
+                        _ => new_value,
+                    }
+                }
+            }
+        };
+
+        let mut value = self.jittered(value);
+        if let Some((ref predicate, inclusive)) = self.until {
+            if predicate(&value) {
+                if !inclusive {
+                    return self.finish();
+                }
+                // this one's the last
+                self.max_iterations = Some(self.iteration);
+            }
+        }
+        if let Some(ref mut budget) = self.budget {
+            value = (budget.spend)(&mut budget.remaining, value);
+        }
+        Some(self.current_value.insert(value).clone())
+    }
+
+    // jumps straight to the nth value where possible, without calculating the ones in between
+    fn nth(&mut self, n: usize) -> Option<B> {
+        if n == 0 {
+            return self.next();
+        }
+
+        // the min skip has to start from the first value, and a peeked or immediate value's
+        // already been worked out, so take those the normal way first
+        if self.peeked.is_some() || self.first_value.is_some() ||
+           (self.min_value.is_some() && !self.min_skipped) {
+            self.next()?;
+            return self.nth(n - 1);
+        }
+
+        // a budget needs to know about every value that's spent from it, and `until` about every
+        // value that might end things
+        if self.budget.is_some() || self.until.is_some() {
+            for _ in 0..n {
+                self.next()?;
+            }
+            return self.next();
+        }
+
+        let target = self.iteration.saturating_add(u64::try_from(n).unwrap_or(u64::MAX));
+        match self.max_iterations {
+            Some(mi) if target >= mi => {
+                self.iteration = mi;
+                None
+            }
+            _ => {
+                self.iteration = target;
+                self.next()
+            }
+        }
+    }
+
+    // the skipped iterations don't count towards max_iterations, so even with a min skip still
+    // to come the number remaining is the count
+    fn count(self) -> usize {
+        match self.remaining() {
+            Some(remaining) if !self.can_end() => {
+                usize::try_from(remaining).unwrap_or(usize::MAX)
+            }
+            _ => self.fold(0, |count, _| count + 1),
+        }
+    }
+
+    // with max_iterations set the last value is just the one at the end, so jump there with
+    // nth.  Like nth, that asks the calculator for the last iteration directly, and skips the
+    // values in between
+    fn last(mut self) -> Option<B> {
+        // a budget, `until` or the calculator can end things before max_iterations, so go one at
+        // a time
+        let remaining = match self.remaining() {
+            Some(remaining) if !self.can_end() => remaining,
+            _ => return self.fold(None, |_, value| Some(value)),
+        };
+
+        match remaining.checked_sub(1).map(usize::try_from) {
+            Some(Ok(n)) => self.nth(n),
+            Some(Err(_)) => self.fold(None, |_, value| Some(value)),
+            None => self.next(),
+        }
+    }
+
+    // the min skip moves iteration and max_iterations along together, so the difference between
+    // them is always the number of values left
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // a budget, `until` or the calculator can end things early, but never late
+        let can_end = self.can_end();
+
+        let remaining = match self.remaining() {
+            Some(remaining) => remaining,
+            None => return (if can_end { 0 } else { usize::MAX }, None),
+        };
+        let upper = usize::try_from(remaining).ok();
+
+        if can_end {
+            (0, upper)
+        } else {
+            (upper.unwrap_or(usize::MAX), upper)
+        }
+    }
+}
+
+// max_iterations only ever moves when a value is produced, and a spent budget stays spent, so
+// once None comes back it keeps coming back
+impl<'a, F, B, J> FusedIterator for BackoffSequenceIterator<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+}
+
+impl<'a, F, B, J> BackoffSequenceIterator<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    /// The iterator as an `ExactSizeIterator`, or `None` if its length isn't known up front.
+    /// That takes a `max_iterations`, and nothing that can end the sequence early: no
+    /// `total_duration`, no `until`, and a calculator that never runs out.  A `min` is fine, as
+    /// the skipped iterations don't count towards `max_iterations`.
+    pub fn exact(self) -> Option<Exact<'a, F, B, J>> {
+        if self.max_iterations.is_none() || self.can_end() {
+            return None;
+        }
+        Some(Exact { inner: self })
+    }
+}
+
+/// A `BackoffSequenceIterator` with a known length, made by `BackoffSequenceIterator::exact`.
+pub struct Exact<'a, F: 'a, B, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+}
+
+impl<'a, F, B, J> Iterator for Exact<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, F, B, J> ExactSizeIterator for Exact<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+}
+
+impl<'a, F, B, J> FusedIterator for Exact<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[allow(dead_code)]
+    fn double(x: u64) -> u64 {
+        x * 2
+    }
+
+    fn base_2_exp_calculator(x: u64) -> u64 {
+        2u64.pow(x as u32) - 1
+    }
+
+    #[test]
+    fn new_builder_with_function_ptr() {
+        let f = &base_2_exp_calculator;
+        let mut _x = BackoffSequence::new(f);
+    }
+
+    #[test]
+    fn new_builder_with_closure_ptr() {
+        let f = &|x| 2u64.pow(x as u32);
+        let mut _x = BackoffSequence::new(&f);
+    }
+
+    #[test]
+    fn new_builder_one_opt() {
+        let f = &base_2_exp_calculator;
+        let mut x = BackoffSequence::new(f);
+        x.min(5);
+    }
+
+    #[test]
+    fn new_builder_all_opt() {
+        let f = &base_2_exp_calculator;
+        let mut x = BackoffSequence::new(f);
+        x.min(5).max(1500).max_iterations(500);
+    }
+
+    #[test]
+    fn new_builder_duration() {
         use std::time::Duration;
         let f = &|x| Duration::new(0, 2u32.pow(x as u32));
 
@@ -230,14 +1358,60 @@ mod tests {
         assert_eq!(1000, x.into_iter().collect::<Vec<_>>().len());
     }
 
+    // older toolchains said "arithmetic operation overflowed", newer ones "attempt to multiply
+    // with overflow"
     #[test]
-    #[should_panic(expected = "arithmetic operation overflowed")]
+    #[should_panic(expected = "overflow")]
     fn unbounded_for_loop() {
         let f = &base_2_exp_calculator;
         for _ in &BackoffSequence::new(f) {
         }
     }
 
+    #[test]
+    fn clamp_to_range() {
+        let f = &|x| x * 10;
+        let mut x = BackoffSequence::new(f);
+        x.max_iterations(4).clamp(20..50);
+        assert_eq!(x.iter().collect::<Vec<_>>(), vec![20, 30, 40, 49]);
+
+        x.clamp((Bound::Excluded(20), Bound::Included(50)));
+        assert_eq!(x.iter().collect::<Vec<_>>(), vec![30, 40, 50, 50]);
+
+        x.clamp(..=25);
+        assert_eq!(x.iter().collect::<Vec<_>>(), vec![10, 20, 25, 25]);
+
+        x.clamp(..);
+        assert_eq!(x.iter().collect::<Vec<_>>(), vec![10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn saturating_plateaus() {
+        let mut backoff = BackoffSequence::from_checked(|x| 2u64.checked_pow(x as u32));
+        backoff.max_iterations(70);
+        assert_eq!(backoff.iter().count(), 63);
+
+        backoff.saturating();
+        assert_eq!(backoff.iter().nth(62), Some(1 << 63));
+        assert_eq!(backoff.iter().nth(63), Some(u64::MAX));
+        assert_eq!(backoff.iter().last(), Some(u64::MAX));
+        assert_eq!(backoff.iter().size_hint(), (70, Some(70)));
+
+        // 2^63 is under the max, then 2^64 overflows
+        backoff.max(u64::MAX - 1);
+        assert_eq!(backoff.iter().last(), Some(u64::MAX - 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn saturating_leaves_other_panics_alone() {
+        let values = [1u64, 2, 4];
+        let f = &|x| values[x as usize];
+        let mut backoff = BackoffSequence::new(f);
+        backoff.saturating().max_iterations(5);
+        backoff.iter().for_each(drop);
+    }
+
     #[test]
     fn exponential() {
         let f = &base_2_exp_calculator;
@@ -257,6 +1431,50 @@ mod tests {
         assert_eq!(v, vec![9, 99, 150, 150]);
     }
 
+    #[test]
+    fn first_value_above_max() {
+        use std::cell::Cell;
+
+        let calls = Cell::new(0);
+        let f = &|x| {
+            calls.set(calls.get() + 1);
+            1000 * x
+        };
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(150).max_iterations(3);
+
+        // the very first value gets clamped, and that's enough to stop calling the calculator
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![150, 150, 150]);
+        assert_eq!(calls.get(), 1);
+
+        // the clamp happens before jitter gets a look, and again after it
+        let v = backoff.jitter_with(|v| v + 1).iter().collect::<Vec<_>>();
+        assert_eq!(v, vec![150, 150, 150]);
+        let v = backoff.jitter_with(|v| v - 1).iter().collect::<Vec<_>>();
+        assert_eq!(v, vec![149, 149, 149]);
+    }
+
+    #[test]
+    fn nan_values() {
+        let f = &|x| if x == 2 { f64::NAN } else { x as f64 };
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(10.0).max_iterations(3);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![1.0, 10.0, 10.0]);
+
+        let f = &|x| x as f64;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(10.0).max_iterations(3);
+        let v = backoff.jitter_with(|v| if v == 2.0 { f64::NAN } else { v })
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![1.0, 10.0, 3.0]);
+
+        // with nothing to clamp it to, it's passed along
+        let f = &|_| f64::NAN;
+        let backoff = BackoffSequence::new(f);
+        assert!(backoff.iter().next().unwrap().is_nan());
+    }
+
     #[test]
     fn min_value() {
         let f = &|x| 10u64.pow(x as u32) - 1;
@@ -280,6 +1498,321 @@ mod tests {
         assert_eq!(v, vec![99, 999, 9999, 10000]);
     }
 
+    #[test]
+    fn min_floors_later_values() {
+        let f = &|x: u64| [50, 150, 120, 80, 200][(x as usize - 1) % 5];
+        let v = BackoffSequence::new(f)
+            .max_iterations(4)
+            .min(100)
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![150, 120, 100, 200]);
+
+        let v = BackoffSequence::new(f)
+            .max_iterations(4)
+            .min(100)
+            .jitter_with(|v| v - 60)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![100, 100, 100, 140]);
+    }
+
+    #[test]
+    fn debug_output() {
+        let f = &|x| x;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(2).max(10).max_iterations(3);
+        assert_eq!(format!("{:?}", backoff),
+                   "BackoffSequence { max_iterations: Some(3), min_value: Some(2), max_value: \
+                    Some(10) }");
+
+        let mut iter = backoff.iter();
+        iter.next();
+        assert_eq!(format!("{:?}", iter),
+                   "BackoffSequenceIterator { iteration: 2, max_iterations: Some(4), \
+                    current_value: Some(2), min_value: Some(2), max_value: Some(10) }");
+    }
+
+    #[test]
+    fn display_summary() {
+        let mut backoff = BackoffSequence::exponential_millis(100, 2.0);
+        backoff.min(Duration::from_millis(100)).max(Duration::from_secs(30)).max_iterations(5);
+        assert_eq!(backoff.to_string(), "exponential, max 5 attempts, clamp [100ms, 30s]");
+
+        let config = BackoffConfig::new(Strategy::Linear {
+            initial: 1u64,
+            increment: 1,
+        });
+        let mut seq = config.into_sequence();
+        seq.max_iterations(1);
+        assert_eq!(seq.to_string(), "linear, max 1 attempt");
+
+        let f = &|x| x;
+        let mut backoff = BackoffSequence::new(f);
+        assert_eq!(backoff.to_string(), "unbounded");
+        backoff.max(10);
+        assert_eq!(backoff.to_string(), "max 10");
+
+        backoff.min_floor(2).immediate_first().skip_first(3).descending().until(|v| *v > 5);
+        assert_eq!(backoff.to_string(),
+                   "floor 2, max 10, immediate first, skip first 3, descending, until");
+        backoff.min(2).until_inclusive(|v| *v > 5);
+        assert_eq!(backoff.to_string(),
+                   "clamp [2, 10], immediate first, skip first 3, descending, until inclusive");
+    }
+
+    #[test]
+    fn max_per_attempt() {
+        let f = &|x| x * 10;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_with(|n| if n < 3 { 15 } else { 35 }).max_iterations(5);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![10, 15, 30, 35, 35]);
+        assert_eq!(backoff.to_string(), "max 5 attempts, max per attempt");
+
+        // a cap that loosens isn't stuck at the first one that was reached
+        let constant = &|_| 100;
+        let mut rising = BackoffSequence::new(constant);
+        rising.max_with(|n| n * 5).max_iterations(3);
+        assert_eq!(rising.iter().collect::<Vec<_>>(), vec![5, 10, 15]);
+
+        // the cap is for the iteration the min skip lands on
+        backoff.min(30).max_with(|n| n * 8).max_iterations(3);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![24, 32, 40]);
+
+        backoff.max(12);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![12, 12, 12]);
+    }
+
+    #[test]
+    fn until_predicate() {
+        let f = &|x| x * 10;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(45).until(|v| *v >= 30).max_iterations(10);
+        assert_eq!(backoff.iter().size_hint(), (0, Some(10)));
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![10, 20]);
+        assert_eq!((backoff.iter().count(), backoff.iter().last()), (2, Some(20)));
+        assert_eq!(backoff.iter().nth(3), None);
+
+        // it sees the values after the jitter and the clamp, so 21 rather than 20, and 30 + 1
+        // as 25
+        backoff.max(25).until_inclusive(|v| *v == 25 || *v == 20);
+        let jittered = backoff.jitter_with(|v| v + 1);
+        assert_eq!(jittered.iter().collect::<Vec<_>>(), vec![11, 21, 25]);
+
+        backoff.max(45).until_inclusive(|v| *v >= 30);
+        let mut iter = backoff.iter();
+        assert_eq!(iter.by_ref().collect::<Vec<_>>(), vec![10, 20, 30]);
+        assert_eq!((iter.next(), iter.remaining()), (None, Some(0)));
+        iter.reset();
+        assert_eq!(iter.last(), Some(30));
+    }
+
+    #[test]
+    fn equality_ignores_calculator() {
+        let f = &|x| x;
+        let g = &|x| x * 2;
+        let mut a = BackoffSequence::new(f);
+        let mut b = BackoffSequence::new(g);
+        a.min(2).max(10);
+        b.max(10).min(2);
+        assert_eq!(a, b);
+
+        b.max_iterations(3);
+        assert_ne!(a, b);
+        assert_eq!(a, a.jitter_with(|v| v + 1));
+    }
+
+    #[test]
+    fn equality_covers_every_setting() {
+        let f = &|x| x;
+        let mut variants = vec![BackoffSequence::new(f)];
+        for setting in 0..7 {
+            let mut seq = BackoffSequence::new(f);
+            match setting {
+                0 => seq.min(2),
+                1 => seq.min_floor(2),
+                2 => seq.max(4),
+                3 => seq.min_search_limit(5),
+                4 => seq.immediate_first(),
+                5 => seq.descending(),
+                _ => seq.skip_first(1),
+            };
+            variants.push(seq);
+        }
+
+        for (i, a) in variants.iter().enumerate() {
+            for (j, b) in variants.iter().enumerate() {
+                assert_eq!(a == b, i == j, "variants {} and {}", i, j);
+            }
+        }
+        assert_ne!(variants[0], *BackoffSequence::new(f).saturating());
+
+        let mut a = BackoffSequence::constant(Duration::from_millis(10));
+        let b = a.clone();
+        a.total_duration(Duration::from_secs(1));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn min_floor_doesnt_skip() {
+        let f = &|x: u64| [50, 150, 120, 80, 200][(x as usize - 1) % 5];
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(5).min_floor(100);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![100, 150, 120, 100, 200]);
+
+        let mut iter = backoff.iter();
+        assert_eq!(iter.nth(1), Some(150));
+        assert_eq!(iter.iteration(), 2);
+        iter.reset();
+        assert_eq!(iter.next(), Some(100));
+
+        // whichever was set last wins
+        backoff.min(100);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![150, 120, 100, 200, 100]);
+        let floored = BackoffSequence::new(f).max_iterations(5).min_floor(100).clone();
+        assert!(backoff != floored);
+    }
+
+    #[test]
+    fn immediate_first_value() {
+        let f = &|x| x * 10;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(20).max_iterations(4).immediate_first();
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![0, 20, 30, 40]);
+        assert_eq!(backoff.iter().size_hint(), (4, Some(4)));
+        assert_eq!(backoff.iter().last(), Some(40));
+        assert_eq!(backoff.iter().nth(2), Some(30));
+
+        let mut iter = backoff.iter();
+        assert_eq!(iter.peek(), Some(&0));
+        assert_eq!((iter.next(), iter.iteration(), iter.remaining()), (Some(0), 0, Some(3)));
+        assert_eq!((iter.next(), iter.iteration()), (Some(20), 2));
+        iter.reset();
+        assert_eq!(iter.next(), Some(0));
+
+        backoff.max_iterations(1);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![0]);
+        backoff.max_iterations(0);
+        assert_eq!(backoff.iter().next(), None);
+    }
+
+    #[test]
+    fn descending_settles_at_min() {
+        // this would underflow past iteration 10, if it kept being called
+        let f = &|x: u64| 1000 - 100 * x;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(300).max(650).max_iterations(12).descending();
+        assert_eq!(backoff.iter().collect::<Vec<_>>(),
+                   vec![650, 650, 650, 600, 500, 400, 300, 300, 300, 300, 300, 300]);
+
+        // without it the max is where things stop
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max(650).max_iterations(5);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![650; 5]);
+
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(850).max_iterations(3).descending();
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![900, 850, 850]);
+    }
+
+    #[test]
+    fn skip_first_lines_up() {
+        let calls = std::cell::Cell::new(0);
+        let f = &|x| {
+            calls.set(calls.get() + 1);
+            x * 10
+        };
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(8).skip_first(3);
+        let mut iter = backoff.iter();
+        assert_eq!(iter.iteration(), 3);
+        assert_eq!(iter.remaining(), Some(5));
+        assert_eq!(iter.next(), Some(40));
+        assert_eq!(calls.get(), 1);
+
+        let unskipped = BackoffSequence::new(f).max_iterations(8).iter().collect::<Vec<_>>();
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), unskipped[3..]);
+        iter.reset();
+        assert_eq!(iter.next(), Some(40));
+
+        // the iterations skipped on the way up to the min still don't count
+        backoff.min(65);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![70, 80, 90, 100, 110]);
+    }
+
+    #[test]
+    fn min_skip_calls_each_iteration_once() {
+        use std::cell::Cell;
+
+        let calls = Cell::new(0);
+        let f = &|x| {
+            calls.set(calls.get() + 1);
+            x * 10
+        };
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(45).max_iterations(2);
+
+        // 10 to 40 are skipped, then 50 and 60 are yielded
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![50, 60]);
+        assert_eq!(calls.get(), 6);
+
+        calls.set(0);
+        backoff.min(5);
+        assert_eq!(backoff.iter().next(), Some(10));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn min_never_reached() {
+        let f = &|_| 0;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(10);
+        let mut iter = backoff.iter();
+        assert_eq!(iter.by_ref().take(3).collect::<Vec<_>>(), vec![10, 10, 10]);
+        assert_eq!(iter.iteration(), 3);
+
+        let f = &|x| x;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(10).min_search_limit(5).max_iterations(3);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![10, 10, 10]);
+
+        backoff.min_search_limit(9);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn min_skip_overflow() {
+        let f = &|x| x;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(5).max_iterations(u64::MAX);
+
+        let mut iter = backoff.iter();
+        assert_eq!(iter.next(), Some(5));
+        assert_eq!(iter.remaining(), Some(u64::MAX - 5));
+
+        // and neither can the search limit
+        let f = &|x| if x > 100 { u64::MAX } else { 0 };
+        let mut other = BackoffSequence::new(f);
+        other.min(u64::MAX).min_search_limit(u64::MAX).max_iterations(2);
+        assert_eq!(other.iter().collect::<Vec<_>>(), vec![u64::MAX, u64::MAX]);
+    }
+
+    #[test]
+    fn iteration_saturates() {
+        let f = &|x| x;
+        let backoff = BackoffSequence::new(f);
+        let mut iter = backoff.iter();
+        // twice, in case usize is smaller than u64
+        iter.nth(usize::MAX);
+        assert_eq!(iter.nth(usize::MAX), Some(u64::MAX));
+        assert_eq!(iter.next(), Some(u64::MAX));
+        assert_eq!(iter.next(), Some(u64::MAX));
+
+        let mut backoff = BackoffSequence::new(f);
+        backoff.skip_first(u64::MAX);
+        assert_eq!(backoff.iter().take(2).collect::<Vec<_>>(), vec![u64::MAX, u64::MAX]);
+    }
+
     #[test]
     fn min_greater_than_max() {
         let f = &|x| 10u64.pow(x as u32) - 1;
@@ -292,6 +1825,38 @@ mod tests {
         assert_eq!(v, vec![100, 100, 100, 100]);
     }
 
+    #[test]
+    fn validation() {
+        let f = &|x| x as f64;
+        let mut backoff = BackoffSequence::new(f);
+        assert_eq!(backoff.validate(), Ok(()));
+
+        backoff.min(5.0).max(4.0);
+        assert_eq!(backoff.validate(), Err(BackoffError::MinAboveMax));
+
+        backoff.max(6.0).max_iterations(0);
+        assert_eq!(backoff.validate(), Err(BackoffError::NoIterations));
+
+        let f = &|_| f64::NAN;
+        assert_eq!(BackoffSequence::new(f).validate(), Err(BackoffError::UnorderedValue));
+    }
+
+    #[test]
+    fn min_lowered_to_max() {
+        let f = &|x| x * 10;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(500).max(100).max_iterations(2);
+
+        // the skip stops at the max, not the min
+        let mut iter = backoff.iter();
+        assert_eq!(iter.next(), Some(100));
+        assert_eq!(iter.iteration(), 10);
+
+        // and the floor can't lift anything past the max either
+        let v = backoff.jitter_with(|v| v - 50).iter().collect::<Vec<_>>();
+        assert_eq!(v, vec![100, 100]);
+    }
+
     #[test]
     fn durations() {
         let f = &|i| Duration::from_millis(2u64.pow(i as u32));
@@ -303,6 +1868,433 @@ mod tests {
         }
     }
 
+    #[test]
+    fn jitter_with_closure() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let v = BackoffSequence::new(f)
+            .max_iterations(4)
+            .min(10)
+            .max(5000)
+            .jitter_with(|v| v * 2)
+            .iter()
+            .collect::<Vec<_>>();
+        // runs after the min skip, and can't go past the max
+        assert_eq!(v, vec![198, 1998, 5000, 5000]);
+    }
+
+    #[test]
+    fn total_duration_budget() {
+        let f = &|i| Duration::from_secs(i);
+        let v = BackoffSequence::new(f)
+            .total_duration(Duration::from_secs(8))
+            .iter()
+            .collect::<Vec<_>>();
+        // 1 + 2 + 3, then the 4 gets cut down to fit
+        assert_eq!(v, vec![1, 2, 3, 2].into_iter().map(Duration::from_secs).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn total_duration_with_max_iterations() {
+        let f = &|_| Duration::from_secs(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.total_duration(Duration::from_secs(10)).max_iterations(3);
+        assert_eq!(backoff.iter().count(), 3);
+
+        backoff.total_duration(Duration::from_secs(0));
+        assert_eq!(backoff.iter().count(), 0);
+    }
+
+    #[test]
+    fn cumulative_total() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(10).max(5000);
+
+        let mut iter = backoff.iter().with_cumulative();
+        assert_eq!(iter.cumulative(), 0);
+        assert_eq!(iter.peek(), Some(&99));
+        assert_eq!(iter.cumulative(), 0);
+        iter.next();
+        assert_eq!(iter.cumulative(), 99);
+        let copy = iter.clone();
+        iter.next();
+        iter.next();
+        assert_eq!(iter.cumulative(), 99 + 999 + 5000);
+        assert_eq!(copy.cumulative(), 99);
+
+        iter.reset();
+        assert_eq!(iter.cumulative(), 0);
+        iter.next();
+        assert_eq!(iter.cumulative(), 99);
+
+        let f = &|_| u64::MAX;
+        let backoff = BackoffSequence::new(f);
+        let mut iter = backoff.iter().with_cumulative();
+        iter.next();
+        iter.next();
+        assert_eq!(iter.cumulative(), u64::MAX);
+    }
+
+    #[test]
+    fn size_hint_tracks_remaining() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        assert_eq!(backoff.iter().size_hint(), (usize::MAX, None));
+
+        backoff.min(100).max_iterations(3);
+        let mut iter = backoff.iter();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        iter.next();
+        iter.next();
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn len_mid_iteration() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(1000).max_iterations(4);
+
+        let mut iter = backoff.iter().exact().unwrap();
+        assert_eq!(iter.len(), 4);
+        iter.next();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.collect::<Vec<_>>(), vec![99999, 999999, 9999999]);
+    }
+
+    #[test]
+    fn exact_only_when_nothing_ends_early() {
+        let f = &|x| x * 10;
+        let mut backoff = BackoffSequence::new(f);
+        assert!(backoff.iter().exact().is_none());
+
+        backoff.max_iterations(4);
+        assert_eq!(backoff.iter().exact().map(|iter| iter.len()), Some(4));
+
+        backoff.until(|value| *value >= 20);
+        assert!(backoff.iter().exact().is_none());
+    }
+
+    #[test]
+    fn stays_finished() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(1000).max_iterations(2);
+
+        let mut iter = backoff.iter();
+        assert_eq!(iter.by_ref().count(), 2);
+        for _ in 0..3 {
+            assert_eq!(iter.next(), None);
+        }
+
+        let f = &|_| Duration::from_secs(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.total_duration(Duration::from_secs(2));
+        let mut iter = backoff.iter();
+        assert_eq!(iter.by_ref().count(), 2);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn cloned_iterator_carries_on() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(10).max(5000).max_iterations(4);
+
+        let mut iter = backoff.iter();
+        iter.next();
+        let copy = iter.clone();
+
+        assert_eq!(iter.next(), Some(999));
+        assert_eq!(iter.next(), Some(5000));
+        assert_eq!(copy.collect::<Vec<_>>(), vec![999, 5000, 5000]);
+    }
+
+    #[test]
+    fn reset_restarts() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(10).max(5000).max_iterations(3);
+
+        let mut iter = backoff.iter();
+        let first = iter.by_ref().collect::<Vec<_>>();
+        assert_eq!(iter.next(), None);
+
+        iter.reset();
+        assert_eq!(iter.remaining(), Some(3));
+        assert_eq!(iter.collect::<Vec<_>>(), first);
+    }
+
+    #[test]
+    fn peek_doesnt_advance() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(10).max_iterations(2);
+
+        let mut iter = backoff.iter();
+        assert_eq!(iter.peek(), Some(&99));
+        assert_eq!(iter.peek(), Some(&99));
+        assert_eq!(iter.remaining(), Some(2));
+
+        assert_eq!(iter.next(), Some(99));
+        assert_eq!(iter.peek(), Some(&999));
+        assert_eq!(iter.next(), Some(999));
+        assert_eq!(iter.peek(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn nth_matches_next() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(10).max(500_000).max_iterations(8);
+
+        for n in 0..10 {
+            let mut stepped = backoff.iter();
+            for _ in 0..n {
+                stepped.next();
+            }
+
+            let mut jumped = backoff.iter();
+            assert_eq!(jumped.nth(n), stepped.next());
+            assert_eq!(jumped.next(), stepped.next());
+            assert_eq!(jumped.size_hint(), stepped.size_hint());
+        }
+    }
+
+    #[test]
+    fn last_matches_stepping() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let stepped = |iter: BackoffSequenceIterator<'_, _, _, _>| iter.fold(None, |_, v| Some(v));
+
+        let mut backoff = BackoffSequence::new(f);
+        for mi in 0..8 {
+            backoff.max_iterations(mi);
+            assert_eq!(backoff.iter().last(), stepped(backoff.iter()));
+            backoff.min(10).max(500_000);
+            assert_eq!(backoff.iter().last(), stepped(backoff.iter()));
+
+            let mut iter = backoff.iter();
+            iter.peek();
+            assert_eq!(iter.clone().last(), stepped(iter));
+            backoff.clamp(..);
+        }
+
+        let checked = BackoffSequence::from_checked(|x| if x < 4 { Some(x) } else { None });
+        assert_eq!(checked.iter().last(), Some(3));
+
+        // only the last value is worked out, so this is quick
+        let mut backoff = BackoffSequence::linear(1u64, 1);
+        backoff.max_iterations(u64::MAX);
+        assert_eq!(backoff.iter().last(), Some(u64::MAX));
+    }
+
+    #[test]
+    fn count_without_iterating() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(100).max_iterations(5);
+        assert_eq!(backoff.iter().count(), 5);
+
+        let mut iter = backoff.iter();
+        iter.next();
+        iter.peek();
+        assert_eq!(iter.count(), 4);
+
+        // a huge max_iterations would take forever to count one by one, and overflow
+        backoff.max_iterations(u64::MAX).max(1000);
+        assert_eq!(backoff.iter().count(), usize::MAX);
+
+        let checked = BackoffSequence::from_checked(|x| if x < 4 { Some(x) } else { None });
+        assert_eq!(checked.iter().count(), 3);
+        let mut budgeted = BackoffSequence::constant(Duration::from_secs(2));
+        budgeted.total_duration(Duration::from_secs(5)).max_iterations(10);
+        assert_eq!(budgeted.iter().count(), 3);
+    }
+
+    #[test]
+    fn totals() {
+        let mut backoff = BackoffSequence::exponential_millis(100, 2.0);
+        backoff.max_iterations(5);
+        assert_eq!(backoff.iter().sum::<Duration>(), Duration::from_millis(3100));
+        assert_eq!(backoff.total(), Duration::from_millis(3100));
+        assert_eq!(backoff.checked_total(), Some(Duration::from_millis(3100)));
+
+        let mut backoff = BackoffSequence::constant(u64::MAX / 2);
+        backoff.max_iterations(3);
+        assert_eq!(backoff.total(), u64::MAX);
+        assert_eq!(backoff.checked_total(), None);
+        backoff.max_iterations(0);
+        assert_eq!(backoff.checked_total(), Some(0));
+    }
+
+    // counts its own clones, to see how many the iterator makes
+    #[derive(Debug, PartialEq, PartialOrd)]
+    struct Counted(u64);
+
+    thread_local! {
+        static CLONES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES.with(|clones| clones.set(clones.get() + 1));
+            Counted(self.0)
+        }
+    }
+
+    #[test]
+    fn clones_per_value() {
+        let clones = || CLONES.with(|clones| clones.replace(0));
+        let f = &|x| Counted(x * 10);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(10);
+
+        // one for each value that's both kept and handed back
+        clones();
+        assert_eq!(backoff.iter().collect::<Vec<_>>().len(), 10);
+        assert_eq!(clones(), 10);
+
+        // the iterator keeps its own copy of the max, and each clamped value is another
+        backoff.max(Counted(55));
+        clones();
+        backoff.iter().for_each(drop);
+        assert_eq!(clones(), 1 + 10 + 5);
+
+        // peeking copies the state it goes back to, but not the state it moves on to
+        let mut iter = backoff.iter();
+        iter.next();
+        clones();
+        iter.peek();
+        assert_eq!(clones(), 1 + 1);
+        iter.next();
+        assert_eq!(clones(), 0);
+    }
+
+    #[test]
+    fn nth_with_budget() {
+        let f = &|i| Duration::from_secs(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.total_duration(Duration::from_secs(8));
+
+        let mut iter = backoff.iter();
+        assert_eq!(iter.nth(3), Some(Duration::from_secs(2)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iteration_and_remaining() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(100);
+
+        let mut iter = backoff.iter();
+        assert_eq!((iter.iteration(), iter.remaining()), (0, None));
+        iter.next();
+        assert_eq!((iter.iteration(), iter.remaining()), (3, None));
+
+        backoff.max_iterations(3);
+        let mut iter = backoff.iter();
+        assert_eq!(iter.remaining(), Some(3));
+        iter.next();
+        iter.next();
+        assert_eq!((iter.iteration(), iter.remaining()), (4, Some(1)));
+    }
+
+    #[test]
+    fn attempts_and_progress() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(100).max_iterations(5);
+
+        let mut iter = backoff.iter();
+        assert_eq!((iter.attempts_remaining(), iter.progress()), (Some(5), Some(0.0)));
+        iter.next();
+        iter.peek();
+        iter.next();
+        // the min skip moved iteration on by two, but only two of the five values are gone
+        assert_eq!(iter.iteration(), 4);
+        assert_eq!((iter.attempts_remaining(), iter.progress()), (Some(3), Some(0.4)));
+        iter.by_ref().for_each(drop);
+        assert_eq!((iter.attempts_remaining(), iter.progress()), (Some(0), Some(1.0)));
+
+        backoff.skip_first(3);
+        let mut iter = backoff.iter();
+        iter.next();
+        assert_eq!((iter.attempts_remaining(), iter.progress()), (Some(1), Some(0.5)));
+
+        let backoff = BackoffSequence::new(f);
+        assert_eq!((backoff.iter().attempts_remaining(), backoff.iter().progress()), (None, None));
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(0);
+        assert_eq!(backoff.iter().progress(), Some(1.0));
+    }
+
+    #[test]
+    fn owned_iter_outlives_sequence() {
+        fn delays() -> BackoffSequenceIterator<'static, Exponential<u64>, u64> {
+            let mut backoff = BackoffSequence::exponential(10, 2.0);
+            backoff.max_iterations(3);
+            backoff.into_owned_iter()
+        }
+
+        assert_eq!(delays().collect::<Vec<_>>(), vec![10, 20, 40]);
+
+        let f = &|x| x * 3;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(2);
+        assert_eq!(backoff.into_owned_iter().collect::<Vec<_>>(), vec![3, 6]);
+    }
+
+    #[test]
+    fn boxed_calculators() {
+        let mut strategies = vec![BackoffSequence::from_boxed(Box::new(|x| x)),
+                                  BackoffSequence::from_boxed(Box::new(|x| x * x))];
+        for backoff in &mut strategies {
+            backoff.max_iterations(3);
+        }
+        strategies.push(strategies[1].clone());
+
+        let v = strategies.iter().map(|s| s.iter().collect::<Vec<_>>()).collect::<Vec<_>>();
+        assert_eq!(v, vec![vec![1, 2, 3], vec![1, 4, 9], vec![1, 4, 9]]);
+    }
+
+    #[test]
+    fn value_without_debug() {
+        // nothing but the bounds the sequence itself needs
+        #[derive(Clone, PartialEq, PartialOrd)]
+        struct Ticks(u64);
+
+        let f = &|x| Ticks(x * 2);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(Ticks(3)).max(Ticks(6)).max_iterations(3);
+
+        let mut iter = backoff.iter();
+        assert!(iter.next() == Some(Ticks(4)));
+        assert!(iter.next() == Some(Ticks(6)));
+        assert!(iter.peek() == Some(&Ticks(6)));
+
+        let mut seen = 0;
+        for value in &backoff {
+            assert!(value.0 >= 4 && value.0 <= 6);
+            seen += 1;
+        }
+        assert_eq!(seen, 3);
+    }
+
+    #[test]
+    fn size_hint_with_budget() {
+        let f = &|_| Duration::from_secs(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.total_duration(Duration::from_secs(2));
+        assert_eq!(backoff.iter().size_hint(), (0, None));
+
+        backoff.max_iterations(5);
+        assert_eq!(backoff.iter().size_hint(), (0, Some(5)));
+    }
+
     #[test]
     #[ignore]
     fn manual_iter_next() {
@@ -0,0 +1,114 @@
+// The `backoff!` macro, for setting up one of the presets in a single line.
+
+/// Makes one of the preset sequences and sets its options, in one go:
+///
+/// ```
+/// # #[macro_use] extern crate backoff_sequence;
+/// # fn main() {
+/// let backoff = backoff!(exponential, initial = 100 ms, factor = 2, max = 30 s, attempts = 5);
+/// assert_eq!(backoff.iter().count(), 5);
+/// # }
+/// ```
+///
+/// The presets are `exponential` (with `initial` and `factor`), `linear` (with `initial` and
+/// `increment`), `constant` (with `value`) and `fibonacci` (with `initial`).  After those can
+/// come any of `min`, `max` and `attempts`, which is `max_iterations`.
+///
+/// A value followed by `ns`, `us`, `ms` or `s` is a `Duration` of that many units.  The unit
+/// has to be separated by a space, `100 ms` and not `100ms`: the compiler reads `100ms` as a
+/// number with an unknown suffix and rejects it before the macro sees it.  Anything else is used
+/// as it is, so `initial = 10` makes a sequence of integers, but longer expressions, including
+/// other `Duration`s, need parentheses, like `max = (Duration::from_secs(30))`.
+#[macro_export]
+macro_rules! backoff {
+    (exponential, initial = $n:tt $($u:ident)?, factor = $factor:expr $(, $($opts:tt)*)?) => {{
+        let mut seq = $crate::BackoffSequence::exponential($crate::__backoff_value!($n $($u)?),
+                                                           $factor as f64);
+        $crate::backoff!(@options seq; $($($opts)*)?);
+        seq
+    }};
+    (linear,
+     initial = $n:tt $($u:ident)?,
+     increment = $i:tt $($v:ident)?
+     $(, $($opts:tt)*)?) => {{
+        let mut seq = $crate::BackoffSequence::linear($crate::__backoff_value!($n $($u)?),
+                                                      $crate::__backoff_value!($i $($v)?));
+        $crate::backoff!(@options seq; $($($opts)*)?);
+        seq
+    }};
+    (constant, value = $n:tt $($u:ident)? $(, $($opts:tt)*)?) => {{
+        let mut seq = $crate::BackoffSequence::constant($crate::__backoff_value!($n $($u)?));
+        $crate::backoff!(@options seq; $($($opts)*)?);
+        seq
+    }};
+    (fibonacci, initial = $n:tt $($u:ident)? $(, $($opts:tt)*)?) => {{
+        let mut seq = $crate::BackoffSequence::fibonacci($crate::__backoff_value!($n $($u)?));
+        $crate::backoff!(@options seq; $($($opts)*)?);
+        seq
+    }};
+
+    (@options $seq:ident;) => {};
+    (@options $seq:ident; min = $n:tt $($u:ident)? $(, $($rest:tt)*)?) => {
+        $seq.min($crate::__backoff_value!($n $($u)?));
+        $crate::backoff!(@options $seq; $($($rest)*)?);
+    };
+    (@options $seq:ident; max = $n:tt $($u:ident)? $(, $($rest:tt)*)?) => {
+        $seq.max($crate::__backoff_value!($n $($u)?));
+        $crate::backoff!(@options $seq; $($($rest)*)?);
+    };
+    (@options $seq:ident; attempts = $n:expr $(, $($rest:tt)*)?) => {
+        $seq.max_iterations($n);
+        $crate::backoff!(@options $seq; $($($rest)*)?);
+    };
+}
+
+// a value for `backoff!`, with or without a unit
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __backoff_value {
+    ($n:tt ns) => { ::core::time::Duration::from_nanos($n) };
+    ($n:tt us) => { ::core::time::Duration::from_micros($n) };
+    ($n:tt ms) => { ::core::time::Duration::from_millis($n) };
+    ($n:tt s) => { ::core::time::Duration::from_secs($n) };
+    ($value:tt) => { $value };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn presets() {
+        let v = backoff!(exponential, initial = 100 ms, factor = 2, max = 1 s, attempts = 5)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, [100, 200, 400, 800, 1000].map(Duration::from_millis));
+
+        let v = backoff!(linear, initial = 10, increment = 5, attempts = 3,)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![10, 15, 20]);
+
+        let v = backoff!(constant, value = 250 us, attempts = 2).iter().collect::<Vec<_>>();
+        assert_eq!(v, vec![Duration::from_micros(250); 2]);
+
+        let v = backoff!(fibonacci, initial = 1 s, min = 2 s, attempts = 3)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, [2, 3, 5].map(Duration::from_secs));
+    }
+
+    #[test]
+    fn expressions() {
+        let initial = Duration::from_millis(50);
+        let attempts = 2;
+        let v = backoff!(exponential,
+                         initial = initial,
+                         factor = 3.0,
+                         max = (Duration::from_millis(100)),
+                         attempts = attempts + 1)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, [50, 100, 100].map(Duration::from_millis));
+    }
+}
@@ -0,0 +1,489 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::ops::Sub;
+
+use super::{BackoffSequence, BackoffSequenceIterator, Calculator, ClampedTo, Jitter, NoJitter,
+            SaturatingAdd};
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
+{
+    /// Iterates over the sequence with each value passed through `f`.  The calculation, `min`,
+    /// `max` and jitter all still happen in `B`, so something like `u64` milliseconds can be
+    /// worked with and then turned into a `Duration` at the end.
+    pub fn map_value<G, C>(&self, f: G) -> MapValue<'_, F, B, G, J>
+        where G: Fn(B) -> C
+    {
+        MapValue {
+            inner: self.iter(),
+            f,
+        }
+    }
+
+    /// Iterates over `(iteration, value)` pairs, where `iteration` is the 1-based iteration
+    /// the value was calculated for.  Unlike `enumerate`, this follows the jump made by the skip
+    /// up to `min`.
+    pub fn with_iteration(&self) -> WithIteration<'_, F, B, J> {
+        WithIteration { inner: self.iter() }
+    }
+
+    /// Iterates over the running totals of the values rather than the values themselves, so
+    /// the `n`th item is how long is spent waiting before attempt `n + 1`.  Totals saturate
+    /// rather than overflow.
+    pub fn cumulative(&self) -> Cumulative<'_, F, B, J> {
+        Cumulative {
+            inner: self.iter(),
+            total: None,
+        }
+    }
+
+    /// Iterates over the values for as long as their running total stays at or below `limit`,
+    /// stopping before the first value that would take it over.  Like `total_duration`, but for
+    /// any `B`, and without the last value cut short unless `clamp_last` is called on the
+    /// result.
+    pub fn take_while_cumulative(&self, limit: B) -> TakeWhileCumulative<'_, F, B, J> {
+        TakeWhileCumulative {
+            inner: self.iter(),
+            limit,
+            total: None,
+            remainder: None,
+            done: false,
+        }
+    }
+
+    /// Iterates over the last `n` values at each step, oldest first, like `slice::windows` but
+    /// for a sequence that's worked out as it goes.  Until there have been `n` values there's
+    /// nothing to yield, unless `partial` is called on the result to get the shorter windows
+    /// too.  Panics if `n` is 0.
+    pub fn windows(&self, n: usize) -> Windows<'_, F, B, J> {
+        assert!(n != 0, "window size must be non-zero");
+        Windows {
+            inner: self.iter(),
+            // the window grows as values come in, so a huge `n` doesn't allocate up front
+            window: VecDeque::new(),
+            size: n,
+            partial: false,
+        }
+    }
+
+    /// Iterates over the values along with what the limits did to them, for logging things like
+    /// "max backoff reached".  Each iteration passed over by the skip up to `min` comes first,
+    /// as a `Skipped`, so there's an item for every iteration the calculator was asked about.
+    pub fn annotated(&self) -> Annotated<'_, F, B, J> {
+        Annotated {
+            inner: self.iter(),
+            skipped: 0,
+            queued: None,
+        }
+    }
+}
+
+/// A value from `BackoffSequence::annotated`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Clamped<B> {
+    /// A value the limits left alone, apart from the jitter.
+    Value(B),
+    /// A value lowered to the `max`, or held there once the sequence has reached it.
+    ClampedMax(B),
+    /// A value raised to the `min`, or held there once a descending sequence has reached it.
+    ClampedMin(B),
+    /// An iteration the skip up to `min` passed over, which has no value.
+    Skipped,
+}
+
+impl<B> Clamped<B> {
+    /// The value the sequence yielded, whether or not it was clamped, or `None` for `Skipped`.
+    pub fn value(self) -> Option<B> {
+        match self {
+            Clamped::Value(value) | Clamped::ClampedMax(value) | Clamped::ClampedMin(value) => {
+                Some(value)
+            }
+            Clamped::Skipped => None,
+        }
+    }
+}
+
+/// The iterator returned by `BackoffSequence::map_value`.
+pub struct MapValue<'a, F: 'a, B, G, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+    f: G,
+}
+
+impl<'a, F, B, J, G, C> Iterator for MapValue<'a, F, B, G, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>,
+          G: Fn(B) -> C
+{
+    type Item = C;
+
+    fn next(&mut self) -> Option<C> {
+        self.inner.next().map(&self.f)
+    }
+}
+
+/// The iterator returned by `BackoffSequence::with_iteration`.
+pub struct WithIteration<'a, F: 'a, B, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+}
+
+impl<'a, F, B, J> Iterator for WithIteration<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    type Item = (u64, B);
+
+    fn next(&mut self) -> Option<(u64, B)> {
+        let value = self.inner.next()?;
+        Some((self.inner.iteration(), value))
+    }
+}
+
+/// The iterator returned by `BackoffSequence::cumulative`.
+pub struct Cumulative<'a, F: 'a, B, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+    total: Option<B>,
+}
+
+impl<'a, F, B, J> Iterator for Cumulative<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone + SaturatingAdd,
+          J: Jitter<B>
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        let value = self.inner.next()?;
+        let total = match self.total {
+            Some(ref total) => total.saturating_add(&value),
+            None => value,
+        };
+        Some(self.total.insert(total).clone())
+    }
+}
+
+impl<'a, F, B, J> BackoffSequenceIterator<'a, F, B, J> {
+    /// Keeps a running total of the values as they're yielded, for showing "total waited so
+    /// far" without tracking it separately.  Adding up the values is the only thing that needs
+    /// `B` to be summable, so it's kept out of the iterator itself.
+    pub fn with_cumulative(self) -> WithCumulative<'a, F, B, J> {
+        WithCumulative {
+            inner: self,
+            total: None,
+        }
+    }
+}
+
+/// The iterator returned by `BackoffSequenceIterator::with_cumulative`.
+pub struct WithCumulative<'a, F: 'a, B, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+    total: Option<B>,
+}
+
+impl<'a, F, B, J> Clone for WithCumulative<'a, F, B, J>
+    where B: Clone,
+          J: Clone
+{
+    fn clone(&self) -> Self {
+        WithCumulative {
+            inner: self.inner.clone(),
+            total: self.total.clone(),
+        }
+    }
+}
+
+impl<'a, F, B, J> WithCumulative<'a, F, B, J>
+    where B: Clone + Default
+{
+    /// The sum of every value yielded so far, saturating rather than overflowing.
+    pub fn cumulative(&self) -> B {
+        self.total.clone().unwrap_or_default()
+    }
+
+    /// `BackoffSequenceIterator::reset`, with the total going back to zero too.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+        self.total = None;
+    }
+}
+
+impl<'a, F, B, J> WithCumulative<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    /// `BackoffSequenceIterator::peek`, which doesn't add to the total.
+    pub fn peek(&mut self) -> Option<&B> {
+        self.inner.peek()
+    }
+}
+
+impl<'a, F, B, J> Iterator for WithCumulative<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone + SaturatingAdd,
+          J: Jitter<B>
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        let value = self.inner.next()?;
+        self.total = Some(match self.total {
+            Some(ref total) => total.saturating_add(&value),
+            None => value.clone(),
+        });
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// The iterator returned by `BackoffSequence::take_while_cumulative`.
+pub struct TakeWhileCumulative<'a, F: 'a, B, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+    limit: B,
+    total: Option<B>,
+    // set by clamp_last, to work out what's left of the limit
+    remainder: Option<fn(&B, &B) -> B>,
+    done: bool,
+}
+
+impl<'a, F, B, J> TakeWhileCumulative<'a, F, B, J>
+    where B: Sub<Output = B> + Clone
+{
+    /// Instead of stopping before the value that would take the total over the limit, cuts that
+    /// value short so the total lands exactly on the limit, and stops after it.
+    pub fn clamp_last(mut self) -> Self {
+        self.remainder = Some(|limit, total| limit.clone() - total.clone());
+        self
+    }
+}
+
+impl<'a, F, B, J> Iterator for TakeWhileCumulative<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone + SaturatingAdd,
+          J: Jitter<B>
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        if self.done {
+            return None;
+        }
+
+        let value = self.inner.next()?;
+        let total = match self.total {
+            Some(ref total) => total.saturating_add(&value),
+            None => value.clone(),
+        };
+        if total <= self.limit {
+            self.total = Some(total);
+            return Some(value);
+        }
+
+        self.done = true;
+        match (self.remainder, &self.total) {
+            (Some(remainder), Some(total)) if *total < self.limit => {
+                Some(remainder(&self.limit, total))
+            }
+            (Some(_), None) => Some(self.limit.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// The iterator returned by `BackoffSequence::windows`.
+pub struct Windows<'a, F: 'a, B, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+    window: VecDeque<B>,
+    size: usize,
+    partial: bool,
+}
+
+impl<'a, F, B, J> Windows<'a, F, B, J> {
+    /// Also yields the windows from the start of the sequence that have fewer than `n` values,
+    /// so that there's one window for every value.
+    pub fn partial(mut self) -> Self {
+        self.partial = true;
+        self
+    }
+}
+
+impl<'a, F, B, J> Iterator for Windows<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    type Item = Vec<B>;
+
+    fn next(&mut self) -> Option<Vec<B>> {
+        loop {
+            let value = self.inner.next()?;
+            if self.window.len() == self.size {
+                self.window.pop_front();
+            }
+            self.window.push_back(value);
+
+            if self.partial || self.window.len() == self.size {
+                return Some(self.window.iter().cloned().collect());
+            }
+        }
+    }
+}
+
+/// The iterator returned by `BackoffSequence::annotated`.
+pub struct Annotated<'a, F: 'a, B, J = NoJitter> {
+    inner: BackoffSequenceIterator<'a, F, B, J>,
+    // the `Skipped`s still to come before `queued`
+    skipped: u64,
+    queued: Option<Clamped<B>>,
+}
+
+impl<'a, F, B, J> Iterator for Annotated<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    type Item = Clamped<B>;
+
+    fn next(&mut self) -> Option<Clamped<B>> {
+        if self.skipped > 0 {
+            self.skipped -= 1;
+            return Some(Clamped::Skipped);
+        }
+        if let Some(queued) = self.queued.take() {
+            return Some(queued);
+        }
+
+        let before = self.inner.iteration();
+        let value = self.inner.next()?;
+        let value = match self.inner.clamped {
+            ClampedTo::Neither => Clamped::Value(value),
+            ClampedTo::Min => Clamped::ClampedMin(value),
+            ClampedTo::Max => Clamped::ClampedMax(value),
+        };
+
+        // anything more than one iteration on was the min skip
+        match self.inner.iteration().saturating_sub(before) {
+            0 | 1 => Some(value),
+            steps => {
+                self.skipped = steps - 2;
+                self.queued = Some(value);
+                Some(Clamped::Skipped)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn millis_to_duration() {
+        let f = &|x| 2u64.pow(x as u32);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(3).max(10).max_iterations(3);
+
+        let v = backoff.map_value(Duration::from_millis).collect::<Vec<_>>();
+        assert_eq!(v, vec![4, 8, 10].into_iter().map(Duration::from_millis).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn iteration_pairs() {
+        let f = &|x| 10u64.pow(x as u32) - 1;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(100).max_iterations(3);
+
+        let v = backoff.with_iteration().collect::<Vec<_>>();
+        assert_eq!(v, vec![(3, 999), (4, 9999), (5, 99999)]);
+    }
+
+    #[test]
+    fn running_totals() {
+        let mut backoff = BackoffSequence::exponential_millis(100, 2.0);
+        backoff.max_iterations(4);
+        let v = backoff.cumulative().collect::<Vec<_>>();
+        assert_eq!(v, [100, 300, 700, 1500].map(Duration::from_millis));
+
+        let mut backoff = BackoffSequence::constant(u8::MAX / 2);
+        backoff.max_iterations(3);
+        assert_eq!(backoff.cumulative().collect::<Vec<_>>(), vec![127, 254, 255]);
+    }
+
+    #[test]
+    fn stops_at_total() {
+        let backoff = BackoffSequence::linear(10u64, 10);
+        let v = backoff.take_while_cumulative(100).collect::<Vec<_>>();
+        assert_eq!(v, vec![10, 20, 30, 40]);
+
+        let v = backoff.take_while_cumulative(110).clamp_last().collect::<Vec<_>>();
+        assert_eq!(v, vec![10, 20, 30, 40, 10]);
+        let v = backoff.take_while_cumulative(5).clamp_last().collect::<Vec<_>>();
+        assert_eq!(v, vec![5]);
+
+        // landing exactly on the limit leaves nothing for a clamped value
+        let v = backoff.take_while_cumulative(60).clamp_last().collect::<Vec<_>>();
+        assert_eq!(v, vec![10, 20, 30]);
+
+        let mut backoff = BackoffSequence::constant(Duration::from_secs(1));
+        backoff.max_iterations(3);
+        let v = backoff.take_while_cumulative(Duration::from_secs(10)).collect::<Vec<_>>();
+        assert_eq!(v, vec![Duration::from_secs(1); 3]);
+    }
+
+    #[test]
+    fn recent_windows() {
+        let mut backoff = BackoffSequence::linear(10u64, 10);
+        backoff.max(30).max_iterations(5);
+
+        let v = backoff.windows(3).collect::<Vec<_>>();
+        assert_eq!(v, vec![vec![10, 20, 30], vec![20, 30, 30], vec![30, 30, 30]]);
+        let v = backoff.windows(2).partial().collect::<Vec<_>>();
+        assert_eq!(v, vec![vec![10], vec![10, 20], vec![20, 30], vec![30, 30], vec![30, 30]]);
+
+        // too short for a single full window
+        assert_eq!(backoff.windows(6).count(), 0);
+        assert_eq!(backoff.windows(6).partial().last(), Some(vec![10, 20, 30, 30, 30]));
+    }
+
+    #[test]
+    fn huge_windows() {
+        let mut backoff = BackoffSequence::linear(10u64, 10);
+        backoff.max_iterations(3);
+        assert_eq!(backoff.windows(usize::MAX).count(), 0);
+        assert_eq!(backoff.windows(usize::MAX).partial().last(), Some(vec![10, 20, 30]));
+    }
+
+    #[test]
+    fn annotated_values() {
+        use super::Clamped::*;
+
+        let f = &|x| x * 10;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(25).max(45).max_iterations(4);
+        let v = backoff.annotated().collect::<Vec<_>>();
+        assert_eq!(v,
+                   vec![Skipped, Skipped, Value(30), Value(40), ClampedMax(45), ClampedMax(45)]);
+        assert_eq!(backoff.annotated().filter_map(Clamped::value).collect::<Vec<_>>(),
+                   backoff.iter().collect::<Vec<_>>());
+
+        backoff.min_floor(25);
+        let v = backoff.annotated().collect::<Vec<_>>();
+        assert_eq!(v, vec![ClampedMin(25), ClampedMin(25), Value(30), Value(40)]);
+
+        // landing on the bound isn't a clamp, but staying there is
+        let f = &|x| 100 - x * 30;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(10).max(60).max_iterations(5).descending();
+        let v = backoff.annotated().collect::<Vec<_>>();
+        assert_eq!(v, vec![ClampedMax(60), Value(40), Value(10), ClampedMin(10), ClampedMin(10)]);
+        backoff.min(20);
+        assert_eq!(backoff.annotated().nth(2), Some(ClampedMin(20)));
+    }
+}
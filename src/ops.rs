@@ -0,0 +1,439 @@
+// Small arithmetic traits that let the presets and jitter work over integers, floats and
+// `Duration` alike.
+
+use core::convert::TryFrom;
+use core::time::Duration;
+
+// `f64::round` and `f64::powf` need `std`, so without it these do the same by hand
+#[cfg(feature = "std")]
+pub(crate) fn round(x: f64) -> f64 {
+    x.round()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn round(x: f64) -> f64 {
+    // every float this big is already a whole number, and wouldn't fit in an i64
+    const WHOLE: f64 = (1u64 << 52) as f64;
+    if !(x > -WHOLE && x < WHOLE) {
+        return x;
+    }
+    let whole = x as i64 as f64;
+    match x - whole {
+        frac if frac >= 0.5 => whole + 1.0,
+        frac if frac <= -0.5 => whole - 1.0,
+        _ => whole,
+    }
+}
+
+// only ever used with whole number exponents
+#[cfg(feature = "std")]
+pub(crate) fn powi(base: f64, exp: u64) -> f64 {
+    base.powf(exp as f64)
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn powi(mut base: f64, mut exp: u64) -> f64 {
+    let mut result = 1.0;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result *= base;
+        }
+        base *= base;
+        exp >>= 1;
+    }
+    result
+}
+
+/// Division by two.
+pub trait Halve {
+    fn halve(&self) -> Self;
+}
+
+macro_rules! halve_int {
+    ($($t:ty)*) => ($(
+        impl Halve for $t {
+            fn halve(&self) -> Self {
+                *self / 2
+            }
+        }
+    )*)
+}
+
+halve_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+impl Halve for f32 {
+    fn halve(&self) -> Self {
+        *self / 2.0
+    }
+}
+
+impl Halve for f64 {
+    fn halve(&self) -> Self {
+        *self / 2.0
+    }
+}
+
+impl Halve for Duration {
+    fn halve(&self) -> Self {
+        *self / 2
+    }
+}
+
+/// Addition, saturating rather than overflowing.
+pub trait SaturatingAdd {
+    fn saturating_add(&self, rhs: &Self) -> Self;
+}
+
+macro_rules! saturating_add_int {
+    ($($t:ty)*) => ($(
+        impl SaturatingAdd for $t {
+            fn saturating_add(&self, rhs: &Self) -> Self {
+                <$t>::saturating_add(*self, *rhs)
+            }
+        }
+    )*)
+}
+
+saturating_add_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+impl SaturatingAdd for f32 {
+    fn saturating_add(&self, rhs: &Self) -> Self {
+        *self + *rhs
+    }
+}
+
+impl SaturatingAdd for f64 {
+    fn saturating_add(&self, rhs: &Self) -> Self {
+        *self + *rhs
+    }
+}
+
+impl SaturatingAdd for Duration {
+    fn saturating_add(&self, rhs: &Self) -> Self {
+        Duration::saturating_add(*self, *rhs)
+    }
+}
+
+/// Addition, giving `None` rather than overflowing.  For floats that's a finite sum that
+/// comes out infinite.
+pub trait CheckedAdd: Sized {
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+}
+
+macro_rules! checked_add_int {
+    ($($t:ty)*) => ($(
+        impl CheckedAdd for $t {
+            fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *rhs)
+            }
+        }
+    )*)
+}
+
+checked_add_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+macro_rules! checked_add_float {
+    ($($t:ty)*) => ($(
+        impl CheckedAdd for $t {
+            fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                let sum = *self + *rhs;
+                if sum.is_infinite() && self.is_finite() && rhs.is_finite() {
+                    None
+                } else {
+                    Some(sum)
+                }
+            }
+        }
+    )*)
+}
+
+checked_add_float!(f32 f64);
+
+impl CheckedAdd for Duration {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Duration::checked_add(*self, *rhs)
+    }
+}
+
+/// Multiplication by a whole number, saturating rather than overflowing.
+pub trait SaturatingMul {
+    fn saturating_mul(&self, rhs: u64) -> Self;
+}
+
+macro_rules! saturating_mul_int {
+    ($($t:ty)*) => ($(
+        impl SaturatingMul for $t {
+            fn saturating_mul(&self, rhs: u64) -> Self {
+                match <$t>::try_from(rhs) {
+                    Ok(rhs) => <$t>::saturating_mul(*self, rhs),
+                    Err(_) if *self == 0 => 0,
+                    Err(_) if *self > 0 => <$t>::MAX,
+                    Err(_) => <$t>::MIN,
+                }
+            }
+        }
+    )*)
+}
+
+saturating_mul_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+impl SaturatingMul for f32 {
+    fn saturating_mul(&self, rhs: u64) -> Self {
+        *self * rhs as f32
+    }
+}
+
+impl SaturatingMul for f64 {
+    fn saturating_mul(&self, rhs: u64) -> Self {
+        *self * rhs as f64
+    }
+}
+
+impl SaturatingMul for Duration {
+    fn saturating_mul(&self, rhs: u64) -> Self {
+        u32::try_from(rhs)
+            .ok()
+            .and_then(|rhs| self.checked_mul(rhs))
+            .unwrap_or(if *self == Duration::ZERO { Duration::ZERO } else { Duration::MAX })
+    }
+}
+
+/// Multiplication by a float, saturating at the bounds of the type rather than overflowing.
+/// Integers are rounded to the nearest whole number.
+pub trait Scale {
+    fn scale(&self, factor: f64) -> Self;
+}
+
+macro_rules! scale_int {
+    ($($t:ty)*) => ($(
+        impl Scale for $t {
+            fn scale(&self, factor: f64) -> Self {
+                // float to int `as` casts saturate, and NaN becomes zero
+                round(*self as f64 * factor) as $t
+            }
+        }
+    )*)
+}
+
+scale_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+impl Scale for f32 {
+    fn scale(&self, factor: f64) -> Self {
+        (*self as f64 * factor) as f32
+    }
+}
+
+impl Scale for f64 {
+    fn scale(&self, factor: f64) -> Self {
+        *self * factor
+    }
+}
+
+impl Scale for Duration {
+    fn scale(&self, factor: f64) -> Self {
+        let secs = self.as_secs_f64() * factor;
+        if secs > 0.0 {
+            Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// The largest value a type can hold.
+pub trait Bounded {
+    fn max_value() -> Self;
+}
+
+macro_rules! bounded {
+    ($($t:ident)*) => ($(
+        impl Bounded for $t {
+            fn max_value() -> Self {
+                $t::MAX
+            }
+        }
+    )*)
+}
+
+bounded!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize f32 f64 Duration);
+
+/// The largest value strictly below this one: one less for integers, a nanosecond less for
+/// `Duration`, and the next float down.  The smallest value a type can hold is its own
+/// predecessor, and so is NaN.
+pub trait Predecessor {
+    fn predecessor(&self) -> Self;
+}
+
+macro_rules! predecessor_int {
+    ($($t:ty)*) => ($(
+        impl Predecessor for $t {
+            fn predecessor(&self) -> Self {
+                <$t>::saturating_sub(*self, 1)
+            }
+        }
+    )*)
+}
+
+predecessor_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+macro_rules! predecessor_float {
+    ($($t:ident)*) => ($(
+        impl Predecessor for $t {
+            fn predecessor(&self) -> Self {
+                if self.is_nan() || *self == $t::NEG_INFINITY {
+                    return *self;
+                }
+                if *self == 0.0 {
+                    // the negative number closest to zero
+                    return -$t::from_bits(1);
+                }
+                let bits = self.to_bits();
+                $t::from_bits(if *self > 0.0 { bits - 1 } else { bits + 1 })
+            }
+        }
+    )*)
+}
+
+predecessor_float!(f32 f64);
+
+impl Predecessor for Duration {
+    fn predecessor(&self) -> Self {
+        self.saturating_sub(Duration::from_nanos(1))
+    }
+}
+
+/// The smallest value strictly above this one, the opposite of `Predecessor`.  The largest
+/// value a type can hold is its own successor, and so is NaN.
+pub trait Successor {
+    fn successor(&self) -> Self;
+}
+
+macro_rules! successor_int {
+    ($($t:ty)*) => ($(
+        impl Successor for $t {
+            fn successor(&self) -> Self {
+                <$t>::saturating_add(*self, 1)
+            }
+        }
+    )*)
+}
+
+successor_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+macro_rules! successor_float {
+    ($($t:ident)*) => ($(
+        impl Successor for $t {
+            fn successor(&self) -> Self {
+                -(-*self).predecessor()
+            }
+        }
+    )*)
+}
+
+successor_float!(f32 f64);
+
+impl Successor for Duration {
+    fn successor(&self) -> Self {
+        Duration::saturating_add(*self, Duration::from_nanos(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halves() {
+        assert_eq!(7u64.halve(), 3);
+        assert_eq!((-8i32).halve(), -4);
+        assert_eq!(3.0f64.halve(), 1.5);
+        assert_eq!(Duration::from_millis(5).halve(), Duration::from_micros(2500));
+    }
+
+    #[test]
+    fn scales() {
+        assert_eq!(10u64.scale(1.5), 15);
+        assert_eq!(10u64.scale(-1.0), 0);
+        assert_eq!(200u8.scale(2.0), 255);
+        assert_eq!(u64::MAX.scale(2.0), u64::MAX);
+        assert_eq!((-10i32).scale(0.25), -3);
+        assert_eq!(Duration::from_millis(100).scale(0.5), Duration::from_millis(50));
+        assert_eq!(Duration::from_secs(u64::MAX).scale(10.0), Duration::MAX);
+        assert_eq!(Duration::from_secs(1).scale(-1.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn saturating_adds() {
+        assert_eq!(SaturatingAdd::saturating_add(&250u8, &10), 255);
+        assert_eq!(SaturatingAdd::saturating_add(&-100i8, &-100), -128);
+        assert_eq!(SaturatingAdd::saturating_add(&Duration::MAX, &Duration::from_secs(1)),
+                   Duration::MAX);
+    }
+
+    #[test]
+    fn checked_adds() {
+        assert_eq!(CheckedAdd::checked_add(&250u8, &5), Some(255));
+        assert_eq!(CheckedAdd::checked_add(&250u8, &10), None);
+        assert_eq!(CheckedAdd::checked_add(&f64::MAX, &f64::MAX), None);
+        assert_eq!(CheckedAdd::checked_add(&f64::INFINITY, &1.0), Some(f64::INFINITY));
+        assert_eq!(CheckedAdd::checked_add(&Duration::MAX, &Duration::from_nanos(1)), None);
+    }
+
+    #[test]
+    fn saturating_muls() {
+        assert_eq!(SaturatingMul::saturating_mul(&7u64, 3), 21);
+        assert_eq!(SaturatingMul::saturating_mul(&200u8, 2), 255);
+        assert_eq!(SaturatingMul::saturating_mul(&1u8, 1000), 255);
+        assert_eq!(SaturatingMul::saturating_mul(&0u8, 1000), 0);
+        assert_eq!(SaturatingMul::saturating_mul(&-2i8, 1000), -128);
+        assert_eq!(SaturatingMul::saturating_mul(&1.5f64, 3), 4.5);
+        assert_eq!(SaturatingMul::saturating_mul(&Duration::from_secs(2), 3),
+                   Duration::from_secs(6));
+        assert_eq!(SaturatingMul::saturating_mul(&Duration::from_secs(1), u64::MAX),
+                   Duration::MAX);
+        assert_eq!(SaturatingMul::saturating_mul(&Duration::MAX, 2), Duration::MAX);
+    }
+
+    #[test]
+    fn float_helpers() {
+        assert_eq!(round(2.5), 3.0);
+        assert_eq!(round(-2.5), -3.0);
+        assert_eq!(round(0.49), 0.0);
+        assert_eq!(round(1e300), 1e300);
+        assert!(round(f64::NAN).is_nan());
+        assert_eq!(powi(1.5, 3), 3.375);
+        assert_eq!(powi(2.0, 0), 1.0);
+        assert_eq!(powi(10.0, 400), f64::INFINITY);
+    }
+
+    #[test]
+    fn max_values() {
+        assert_eq!(<u8 as Bounded>::max_value(), 255);
+        assert_eq!(<f64 as Bounded>::max_value(), f64::MAX);
+        assert_eq!(<Duration as Bounded>::max_value(), Duration::MAX);
+    }
+
+    #[test]
+    fn predecessors() {
+        assert_eq!(5u64.predecessor(), 4);
+        assert_eq!(0u8.predecessor(), 0);
+        assert_eq!(i32::MIN.predecessor(), i32::MIN);
+        assert_eq!(Duration::from_secs(1).predecessor(), Duration::from_nanos(999_999_999));
+        assert_eq!(Duration::ZERO.predecessor(), Duration::ZERO);
+        assert!(1.0f64.predecessor() < 1.0 && 1.0 - 1.0f64.predecessor() == f64::EPSILON / 2.0);
+        assert!(0.0f64.predecessor() < 0.0);
+        assert!((-1.0f32).predecessor() < -1.0);
+        assert!(f64::NAN.predecessor().is_nan());
+    }
+
+    #[test]
+    fn successors() {
+        assert_eq!(5u64.successor(), 6);
+        assert_eq!(u8::MAX.successor(), u8::MAX);
+        assert_eq!(Duration::from_nanos(1).successor(), Duration::from_nanos(2));
+        assert_eq!(Duration::MAX.successor(), Duration::MAX);
+        assert_eq!(1.0f64.successor() - 1.0, f64::EPSILON);
+        assert!((-0.0f64).successor() > 0.0);
+        assert_eq!(f32::INFINITY.successor(), f32::INFINITY);
+    }
+}
@@ -0,0 +1,219 @@
+use core::fmt;
+use core::fmt::Debug;
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+
+use super::{BackoffSequence, BackoffSequenceIterator, Calculator, Jitter, NoJitter};
+
+/// A `BackoffSequence` that owns its calculator, so there's no lifetime to worry about and no
+/// need for `let f = &|x| ...` first.  The calculator can't borrow anything itself, so closures
+/// need to `move` whatever they capture.  It can be returned from functions, and iterated over by
+/// value as well as by reference.  All of the `BackoffSequence` methods are available through
+/// `Deref`.
+pub struct OwnedBackoffSequence<F: 'static, B, J = NoJitter>(BackoffSequence<'static, F, B, J>);
+
+impl<F, B, J> Clone for OwnedBackoffSequence<F, B, J>
+    where B: Clone,
+          J: Clone
+{
+    fn clone(&self) -> Self {
+        OwnedBackoffSequence(self.0.clone())
+    }
+}
+
+impl<F, B> OwnedBackoffSequence<F, B>
+    where F: Fn(u64) -> B,
+          B: PartialOrd + Clone
+{
+    pub fn new(f: F) -> Self {
+        OwnedBackoffSequence(BackoffSequence::shared(f))
+    }
+}
+
+impl<F, B, J> From<BackoffSequence<'static, F, B, J>> for OwnedBackoffSequence<F, B, J> {
+    fn from(seq: BackoffSequence<'static, F, B, J>) -> Self {
+        OwnedBackoffSequence(seq)
+    }
+}
+
+impl<F, B, J> Deref for OwnedBackoffSequence<F, B, J> {
+    type Target = BackoffSequence<'static, F, B, J>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<F, B, J> DerefMut for OwnedBackoffSequence<F, B, J> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<F, B, J> Debug for OwnedBackoffSequence<F, B, J>
+    where B: Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<F, B, J> fmt::Display for OwnedBackoffSequence<F, B, J>
+    where F: Calculator<B>,
+          B: Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<F, B, J> IntoIterator for OwnedBackoffSequence<F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
+{
+    type Item = B;
+    type IntoIter = BackoffSequenceIterator<'static, F, B, J>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_owned_iter()
+    }
+}
+
+impl<'a, F, B, J> IntoIterator for &'a OwnedBackoffSequence<F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
+{
+    type Item = B;
+    type IntoIter = BackoffSequenceIterator<'a, F, B, J>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Lets an `FnMut(u64) -> B` closure be used as a calculator, by keeping it behind a lock.
+/// Created by `BackoffSequence::from_fn_mut`.
+#[cfg(feature = "std")]
+pub struct FnMutCalculator<G>(Mutex<G>);
+
+#[cfg(feature = "std")]
+impl<G, B> Calculator<B> for FnMutCalculator<G>
+    where G: FnMut(u64) -> B
+{
+    fn calculate(&self, iteration: u64) -> B {
+        let mut f = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        (*f)(iteration)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<G, B> BackoffSequence<'static, FnMutCalculator<G>, B>
+    where G: FnMut(u64) -> B,
+          B: PartialOrd + Clone
+{
+    /// A sequence whose calculator can keep state between calls, like a counter or its own
+    /// random number generator.  The closure is owned by the sequence, and shared by every
+    /// iterator made from it (or from its clones), so they all see the same state.
+    pub fn from_fn_mut(f: G) -> Self {
+        BackoffSequence::shared(FnMutCalculator(Mutex::new(f)))
+    }
+}
+
+/// Lets an `Fn(u64) -> Option<B>` closure be used as a calculator, with `None` ending the
+/// sequence.  Created by `BackoffSequence::from_checked`.
+pub struct CheckedCalculator<G>(G);
+
+impl<G, B> Calculator<B> for CheckedCalculator<G>
+    where G: Fn(u64) -> Option<B>
+{
+    /// Panics if the closure returns `None`; the iterator uses `try_calculate` instead.
+    fn calculate(&self, iteration: u64) -> B {
+        (self.0)(iteration).expect("calculator ended the sequence")
+    }
+
+    fn try_calculate(&self, iteration: u64) -> Option<B> {
+        (self.0)(iteration)
+    }
+
+    fn can_end(&self) -> bool {
+        true
+    }
+}
+
+impl<G, B> BackoffSequence<'static, CheckedCalculator<G>, B>
+    where G: Fn(u64) -> Option<B>,
+          B: PartialOrd + Clone
+{
+    /// A sequence that ends as soon as the calculator returns `None`, such as when
+    /// `checked_pow` overflows.  This happens before any `min` or `max` is applied, and a
+    /// `None` during the skip up to `min` ends the sequence too.
+    pub fn from_checked(f: G) -> Self {
+        BackoffSequence::shared(CheckedCalculator(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capped(max: u64) -> OwnedBackoffSequence<impl Fn(u64) -> u64, u64> {
+        let mut backoff = OwnedBackoffSequence::new(move |x| 10u64.pow(x as u32) * max / 1000);
+        backoff.max(max).max_iterations(4);
+        backoff
+    }
+
+    #[test]
+    fn returned_from_function() {
+        let backoff = capped(500);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![5, 50, 500, 500]);
+
+        let mut sum = 0;
+        for x in &backoff {
+            sum += x;
+        }
+        assert_eq!(sum, 1055);
+
+        assert_eq!(backoff.into_iter().count(), 4);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn stateful_calculator() {
+        let mut calls = 0;
+        let mut backoff = BackoffSequence::from_fn_mut(move |x| {
+            calls += 1;
+            x * 10 + calls
+        });
+        backoff.max_iterations(3);
+
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![11, 22, 33]);
+        // the state carries over to the next iterator
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![14, 25, 36]);
+    }
+
+    #[test]
+    fn checked_calculator_ends() {
+        let backoff = BackoffSequence::from_checked(|x| 10u8.checked_pow(x as u32));
+        let mut iter = backoff.iter();
+        assert_eq!(iter.size_hint(), (0, None));
+        assert_eq!(iter.by_ref().collect::<Vec<_>>(), vec![10, 100]);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.remaining(), Some(0));
+
+        let mut backoff = BackoffSequence::from_checked(|x| 10u8.checked_pow(x as u32));
+        backoff.min(50).max(60);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![60]);
+
+        backoff.min(200).max(u8::MAX);
+        assert_eq!(backoff.iter().next(), None);
+    }
+
+    #[test]
+    fn from_preset() {
+        let backoff = OwnedBackoffSequence::from(BackoffSequence::constant(7u64));
+        assert_eq!(backoff.into_iter().take(2).collect::<Vec<_>>(), vec![7, 7]);
+    }
+}
@@ -0,0 +1,87 @@
+// A bounded sequence worked out once and kept, for when the calculator's too expensive to run
+// again on every pass.
+
+use alloc::vec::Vec;
+use core::iter::Cloned;
+use core::slice;
+
+use super::{BackoffError, BackoffSequence, Calculator, Jitter};
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
+{
+    /// Works out every value of the sequence once, so that it can be replayed as many times as
+    /// wanted without calling the calculator again.  Jitter is applied once too, so every
+    /// replay has the same values.  This needs `max_iterations`, and keeps all of the values
+    /// in memory, so a large one costs as much memory as it saves in calls.  Nothing caps it:
+    /// with one like `u64::MAX` this only works if the calculator or `until` ends the sequence
+    /// long before that, and otherwise it panics or runs out of memory trying to hold them all.
+    pub fn precompute(&self) -> Result<Precomputed<B>, BackoffError> {
+        if self.max_iterations.is_none() {
+            return Err(BackoffError::Unbounded);
+        }
+        Ok(Precomputed(self.iter().collect()))
+    }
+}
+
+/// The values of a sequence, made by `BackoffSequence::precompute`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Precomputed<B>(Vec<B>);
+
+impl<B> Precomputed<B>
+    where B: Clone
+{
+    /// Replays the values from the start.
+    pub fn iter(&self) -> Cloned<slice::Iter<'_, B>> {
+        self.0.iter().cloned()
+    }
+
+    /// All of the values, in order, without cloning them.
+    pub fn values(&self) -> &[B] {
+        &self.0
+    }
+}
+
+impl<'a, B> IntoIterator for &'a Precomputed<B>
+    where B: Clone
+{
+    type Item = B;
+    type IntoIter = Cloned<slice::Iter<'a, B>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn calculates_once() {
+        let calls = Cell::new(0);
+        let f = &|x| {
+            calls.set(calls.get() + 1);
+            x * 10
+        };
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(4);
+
+        let values = backoff.precompute().unwrap();
+        for _ in 0..3 {
+            assert_eq!(values.iter().collect::<Vec<_>>(), vec![10, 20, 30, 40]);
+        }
+        assert_eq!(calls.get(), 4);
+        assert_eq!(values.values(), &[10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn needs_max_iterations() {
+        let f = &|x| x;
+        let backoff = BackoffSequence::new(f);
+        assert_eq!(backoff.precompute().err(), Some(BackoffError::Unbounded));
+    }
+}
@@ -0,0 +1,590 @@
+// Ready made calculators for the common shapes of backoff, so that they don't each need a
+// hand written closure.
+
+use core::convert::{TryFrom, TryInto};
+use core::ops::{Range, RangeInclusive};
+use core::time::Duration;
+
+use super::ops;
+#[cfg(feature = "rand")]
+use rand::distributions::uniform::SampleUniform;
+#[cfg(feature = "rand")]
+use rand::rngs::StdRng;
+
+#[cfg(feature = "rand")]
+use super::{EqualJitter, FullJitter};
+use super::{BackoffSequence, Calculator, OwnedBackoffSequence, Predecessor, SaturatingAdd,
+            SaturatingMul, Scale};
+
+/// `initial * factor^(n - 1)`, saturating instead of overflowing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Exponential<B> {
+    initial: B,
+    factor: f64,
+}
+
+impl<B> Calculator<B> for Exponential<B>
+    where B: Scale
+{
+    fn calculate(&self, iteration: u64) -> B {
+        self.initial.scale(ops::powi(self.factor, iteration.saturating_sub(1)))
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("exponential")
+    }
+}
+
+impl<B> BackoffSequence<'static, Exponential<B>, B>
+    where B: Scale + PartialOrd + Clone
+{
+    /// Starts at `initial` and multiplies by `factor` on each iteration.  Values that would
+    /// overflow saturate at the largest value `B` can hold instead.
+    pub fn exponential(initial: B, factor: f64) -> Self {
+        BackoffSequence::shared(Exponential { initial, factor })
+    }
+
+    /// Shorthand for `exponential(initial, factor)` with a `max` of `cap`, so the values grow
+    /// until they reach `cap` and then plateau there.
+    pub fn capped_exponential(initial: B, factor: f64, cap: B) -> Self {
+        let mut seq = BackoffSequence::exponential(initial, factor);
+        seq.max(cap);
+        seq
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<B> BackoffSequence<'static, Exponential<B>, B, FullJitter>
+    where B: Scale + SampleUniform + PartialOrd + Default + Clone
+{
+    /// The "full jitter" recipe from the AWS Architecture Blog: a random value between zero and
+    /// `min(cap, base * 2^n)` for attempts `n = 0, 1, 2, ...`.  It's a sequence like any other, so
+    /// `max_iterations` and the rest can still be set on it.
+    pub fn aws_full_jitter(base: B, cap: B) -> Self {
+        BackoffSequence::capped_exponential(base, 2.0, cap).full_jitter()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<B> BackoffSequence<'static, Exponential<B>, B, FullJitter<StdRng>>
+    where B: Scale + SampleUniform + PartialOrd + Default + Clone
+{
+    /// `aws_full_jitter`, drawing from an RNG seeded with `seed`, so that every iterator
+    /// produces the same values.
+    pub fn aws_full_jitter_seeded(base: B, cap: B, seed: u64) -> Self {
+        BackoffSequence::capped_exponential(base, 2.0, cap).full_jitter_seeded(seed)
+    }
+}
+
+impl BackoffSequence<'static, Exponential<Duration>, Duration> {
+    /// `exponential` starting from `initial_ms` milliseconds.
+    pub fn exponential_millis(initial_ms: u64, factor: f64) -> Self {
+        BackoffSequence::exponential(Duration::from_millis(initial_ms), factor)
+    }
+
+    /// `exponential` starting from `initial_secs` seconds.
+    pub fn exponential_secs(initial_secs: u64, factor: f64) -> Self {
+        BackoffSequence::exponential(Duration::from_secs(initial_secs), factor)
+    }
+}
+
+/// `start..=end` doubles from `start`, with `start` as the `min` and `end` as the `max`, so the
+/// values plateau at `end`.  `(100..=5000).into()` is `exponential(100, 2.0)` with
+/// `.min(100).max(5000)`.
+impl<B> From<RangeInclusive<B>> for BackoffSequence<'static, Exponential<B>, B>
+    where B: Scale + PartialOrd + Clone
+{
+    fn from(range: RangeInclusive<B>) -> Self {
+        let (start, end) = range.into_inner();
+        let mut seq = BackoffSequence::capped_exponential(start.clone(), 2.0, end);
+        seq.min(start);
+        seq
+    }
+}
+
+/// `start..end` is the same as `start..=end`, except that the `max` is the largest value below
+/// `end` (see `Predecessor`), so `end` itself is never reached.
+impl<B> From<Range<B>> for BackoffSequence<'static, Exponential<B>, B>
+    where B: Scale + Predecessor + PartialOrd + Clone
+{
+    fn from(range: Range<B>) -> Self {
+        BackoffSequence::from(range.start..=range.end.predecessor())
+    }
+}
+
+/// A reasonable policy for when there's no reason to tune one: 100ms doubling on each attempt up
+/// to 30s, for at most 10 attempts.  That's 9 waits adding up to a little under a minute.
+impl Default for OwnedBackoffSequence<Exponential<Duration>, Duration> {
+    fn default() -> Self {
+        let mut seq = BackoffSequence::capped_exponential(Duration::from_millis(100),
+                                                          2.0,
+                                                          Duration::from_secs(30));
+        seq.max_iterations(10);
+        OwnedBackoffSequence::from(seq)
+    }
+}
+
+/// The same policy as the unjittered default, with `equal_jitter` so that clients started
+/// together don't all retry together.
+#[cfg(feature = "rand")]
+impl Default for OwnedBackoffSequence<Exponential<Duration>, Duration, EqualJitter> {
+    fn default() -> Self {
+        let seq = OwnedBackoffSequence::<Exponential<Duration>, Duration>::default();
+        OwnedBackoffSequence::from(seq.equal_jitter())
+    }
+}
+
+/// `base^n`, saturating at `u64::MAX` instead of overflowing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExponentialBase(u64);
+
+impl Calculator<u64> for ExponentialBase {
+    fn calculate(&self, iteration: u64) -> u64 {
+        u32::try_from(iteration)
+            .ok()
+            .and_then(|n| self.0.checked_pow(n))
+            .unwrap_or(u64::MAX)
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("exponential")
+    }
+}
+
+impl BackoffSequence<'static, ExponentialBase, u64> {
+    /// `base^n` for each iteration `n`.  Once that no longer fits in a `u64` the values stay at
+    /// `u64::MAX`, or whatever `max` has been set to.
+    pub fn exponential_base(base: u64) -> Self {
+        BackoffSequence::shared(ExponentialBase(base))
+    }
+}
+
+/// `first * ratio^(n - 1)` using exact integer math, saturating instead of overflowing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Geometric<B> {
+    first: B,
+    ratio: u64,
+}
+
+impl<B> Calculator<B> for Geometric<B>
+    where B: SaturatingMul
+{
+    fn calculate(&self, iteration: u64) -> B {
+        let exp = u32::try_from(iteration.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.first.saturating_mul(self.ratio.saturating_pow(exp))
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("geometric")
+    }
+}
+
+impl<B> BackoffSequence<'static, Geometric<B>, B>
+    where B: SaturatingMul + TryInto<u64> + PartialOrd + Clone
+{
+    /// Starts at `first` and multiplies by `ratio` on each iteration.  Unlike `exponential` this
+    /// never goes through floating point, so integer sequences are exact.  A negative `ratio` is
+    /// treated as zero.
+    pub fn geometric(first: B, ratio: B) -> Self {
+        BackoffSequence::shared(Geometric {
+            first,
+            ratio: ratio.try_into().unwrap_or(0),
+        })
+    }
+}
+
+/// `coefficient * n^degree`, saturating instead of overflowing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial<B> {
+    coefficient: B,
+    degree: u32,
+}
+
+impl<B> Calculator<B> for Polynomial<B>
+    where B: SaturatingMul
+{
+    fn calculate(&self, iteration: u64) -> B {
+        self.coefficient.saturating_mul(iteration.saturating_pow(self.degree))
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("polynomial")
+    }
+}
+
+impl<B> BackoffSequence<'static, Polynomial<B>, B>
+    where B: SaturatingMul + PartialOrd + Clone
+{
+    /// `coefficient * n^degree` for each iteration `n`, e.g. a degree of 2 for a quadratic ramp.
+    /// Values that would overflow saturate, and so end up at `max` if it's set.
+    pub fn polynomial(coefficient: B, degree: u32) -> Self {
+        BackoffSequence::shared(Polynomial { coefficient, degree })
+    }
+}
+
+/// `initial + increment * (n - 1)`, saturating instead of overflowing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Linear<B> {
+    initial: B,
+    increment: B,
+}
+
+impl<B> Calculator<B> for Linear<B>
+    where B: SaturatingAdd + SaturatingMul
+{
+    fn calculate(&self, iteration: u64) -> B {
+        let steps = self.increment.saturating_mul(iteration.saturating_sub(1));
+        self.initial.saturating_add(&steps)
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("linear")
+    }
+}
+
+impl<B> BackoffSequence<'static, Linear<B>, B>
+    where B: SaturatingAdd + SaturatingMul + PartialOrd + Clone
+{
+    /// Starts at `initial` and adds `increment` on each iteration.
+    pub fn linear(initial: B, increment: B) -> Self {
+        BackoffSequence::shared(Linear { initial, increment })
+    }
+}
+
+/// `(initial, increment)` is `linear(initial, increment)`, which with a zero `increment` is the
+/// same as `constant(initial)`.
+impl<B> From<(B, B)> for BackoffSequence<'static, Linear<B>, B>
+    where B: SaturatingAdd + SaturatingMul + PartialOrd + Clone
+{
+    fn from((initial, increment): (B, B)) -> Self {
+        BackoffSequence::linear(initial, increment)
+    }
+}
+
+impl BackoffSequence<'static, Linear<Duration>, Duration> {
+    /// `linear` with both values in milliseconds.
+    pub fn linear_millis(initial_ms: u64, increment_ms: u64) -> Self {
+        BackoffSequence::linear(Duration::from_millis(initial_ms),
+                                Duration::from_millis(increment_ms))
+    }
+
+    /// `linear` with both values in seconds.
+    pub fn linear_secs(initial_secs: u64, increment_secs: u64) -> Self {
+        BackoffSequence::linear(Duration::from_secs(initial_secs),
+                                Duration::from_secs(increment_secs))
+    }
+}
+
+/// The same value every time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constant<B>(B);
+
+impl<B> Calculator<B> for Constant<B>
+    where B: Clone
+{
+    fn calculate(&self, _: u64) -> B {
+        self.0.clone()
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("constant")
+    }
+}
+
+impl<B> BackoffSequence<'static, Constant<B>, B>
+    where B: PartialOrd + Clone
+{
+    /// Yields `value` on every iteration, until `max_iterations` runs out.
+    pub fn constant(value: B) -> Self {
+        BackoffSequence::shared(Constant(value))
+    }
+}
+
+/// `initial` times the `n`th Fibonacci number (1, 1, 2, 3, 5, 8, ...), saturating instead of
+/// overflowing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fibonacci<B>(B);
+
+// the nth Fibonacci number, or u64::MAX once they no longer fit.  Worked out from scratch each
+// time rather than carried along, so that it doesn't matter which iteration gets asked for.
+fn fibonacci(n: u64) -> u64 {
+    // fib(93) is the largest one that fits
+    if n > 93 {
+        return u64::MAX;
+    }
+
+    let (mut a, mut b) = (0u64, 1u64);
+    for _ in 0..n {
+        let next = a.saturating_add(b);
+        a = b;
+        b = next;
+    }
+    a
+}
+
+impl<B> Calculator<B> for Fibonacci<B>
+    where B: SaturatingMul
+{
+    fn calculate(&self, iteration: u64) -> B {
+        self.0.saturating_mul(fibonacci(iteration))
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        Some("fibonacci")
+    }
+}
+
+impl<B> BackoffSequence<'static, Fibonacci<B>, B>
+    where B: SaturatingMul + PartialOrd + Clone
+{
+    /// Grows along the Fibonacci sequence, scaled by `initial`.  This grows faster than
+    /// `linear`, but not as quickly as `exponential`.
+    pub fn fibonacci(initial: B) -> Self {
+        BackoffSequence::shared(Fibonacci(initial))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BackoffError;
+    use std::time::Duration;
+
+    #[test]
+    fn exponential() {
+        let v = BackoffSequence::exponential(100u64, 2.0)
+            .max_iterations(5)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![100, 200, 400, 800, 1600]);
+
+        let v = BackoffSequence::exponential(Duration::from_millis(10), 1.5)
+            .max_iterations(3)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v,
+                   vec![Duration::from_millis(10),
+                        Duration::from_millis(15),
+                        Duration::from_micros(22500)]);
+    }
+
+    #[test]
+    fn exponential_saturates() {
+        let mut backoff = BackoffSequence::exponential(1u64, 10.0);
+        backoff.max_iterations(30);
+        assert_eq!(backoff.iter().last(), Some(u64::MAX));
+
+        backoff.max(1_000_000);
+        assert_eq!(backoff.iter().last(), Some(1_000_000));
+    }
+
+    #[test]
+    fn exponential_duration_saturates() {
+        let mut backoff = BackoffSequence::exponential_secs(1, 1e6);
+        backoff.min(Duration::from_secs(3600)).max_iterations(100);
+        assert_eq!(backoff.iter().next(), Some(Duration::from_secs(1_000_000)));
+        assert_eq!(backoff.iter().last(), Some(Duration::MAX));
+
+        backoff.max(Duration::from_secs(7200));
+        assert_eq!(backoff.iter().last(), Some(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn linear() {
+        let v = BackoffSequence::linear(100u64, 50)
+            .max_iterations(4)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![100, 150, 200, 250]);
+
+        let v = BackoffSequence::linear(Duration::from_millis(100), Duration::from_millis(50))
+            .min(Duration::from_millis(175))
+            .max(Duration::from_millis(300))
+            .max_iterations(4)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v,
+                   vec![Duration::from_millis(200),
+                        Duration::from_millis(250),
+                        Duration::from_millis(300),
+                        Duration::from_millis(300)]);
+    }
+
+    #[test]
+    fn constant() {
+        let mut backoff = BackoffSequence::constant(Duration::from_millis(250));
+        backoff.max_iterations(3);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![Duration::from_millis(250); 3]);
+
+        let v = backoff.jitter_with(|d| d / 5 * 4).iter().collect::<Vec<_>>();
+        assert_eq!(v, vec![Duration::from_millis(200); 3]);
+    }
+
+    #[test]
+    fn fibonacci() {
+        let v = BackoffSequence::fibonacci(10u64)
+            .max_iterations(8)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![10, 10, 20, 30, 50, 80, 130, 210]);
+    }
+
+    #[test]
+    fn fibonacci_saturates() {
+        let mut backoff = BackoffSequence::fibonacci(Duration::from_secs(1));
+        backoff.max_iterations(200);
+        assert_eq!(backoff.iter().last(), Some(Duration::MAX));
+
+        backoff.max(Duration::from_secs(60));
+        assert_eq!(backoff.iter().last(), Some(Duration::from_secs(60)));
+        assert_eq!(super::fibonacci(93), 12200160415121876738);
+        assert_eq!(super::fibonacci(94), u64::MAX);
+    }
+
+    #[test]
+    fn exponential_base() {
+        let v = BackoffSequence::exponential_base(3)
+            .max_iterations(4)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![3, 9, 27, 81]);
+
+        let v = BackoffSequence::exponential_base(1000)
+            .max(u64::MAX - 1)
+            .max_iterations(8)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(&v[5..], &[1_000_000_000_000_000_000, u64::MAX - 1, u64::MAX - 1]);
+    }
+
+    #[test]
+    fn geometric() {
+        let v = BackoffSequence::geometric(3u64, 2)
+            .max_iterations(4)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![3, 6, 12, 24]);
+
+        let v = BackoffSequence::geometric(100u8, 3)
+            .max_iterations(3)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![100, 255, 255]);
+    }
+
+    #[test]
+    fn polynomial() {
+        let v = BackoffSequence::polynomial(5u64, 2)
+            .max_iterations(4)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![5, 20, 45, 80]);
+
+        let v = BackoffSequence::polynomial(Duration::from_millis(1), 3)
+            .max(Duration::from_millis(100))
+            .max_iterations(5)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v,
+                   vec![Duration::from_millis(1),
+                        Duration::from_millis(8),
+                        Duration::from_millis(27),
+                        Duration::from_millis(64),
+                        Duration::from_millis(100)]);
+
+        let mut backoff = BackoffSequence::polynomial(2u32, 40);
+        backoff.max(1_000_000).max_iterations(3);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![2, 1_000_000, 1_000_000]);
+    }
+
+    #[test]
+    fn capped_exponential() {
+        let v = BackoffSequence::capped_exponential(100u64, 2.0, 500)
+            .max_iterations(5)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn from_ranges_and_tuples() {
+        let mut backoff: BackoffSequence<_, _> = (100..5000).into();
+        backoff.max_iterations(8);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(),
+                   vec![100, 200, 400, 800, 1600, 3200, 4999, 4999]);
+
+        let mut backoff = BackoffSequence::from(Duration::from_secs(1)..=Duration::from_secs(3));
+        backoff.max_iterations(3);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), [1, 2, 3].map(Duration::from_secs));
+        let (low, high) = (5, 1);
+        assert_eq!(BackoffSequence::from(low..=high).validate().err(),
+                   Some(BackoffError::MinAboveMax));
+
+        let mut backoff = BackoffSequence::from((10u64, 5));
+        backoff.max_iterations(3);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![10, 15, 20]);
+        let mut backoff = BackoffSequence::from((Duration::from_secs(2), Duration::ZERO));
+        backoff.max_iterations(2);
+        assert_eq!(backoff.iter().collect::<Vec<_>>(), vec![Duration::from_secs(2); 2]);
+    }
+
+    #[test]
+    fn default_policy() {
+        let backoff = OwnedBackoffSequence::<Exponential<Duration>, Duration>::default();
+        let v = backoff.iter().collect::<Vec<_>>();
+        assert_eq!(v.len(), 10);
+        assert_eq!(v[..3], [100, 200, 400].map(Duration::from_millis));
+        assert_eq!(v[9], Duration::from_secs(30));
+
+        #[cfg(feature = "rand")]
+        {
+            let jittered = OwnedBackoffSequence::<_, _, EqualJitter>::default();
+            for (j, v) in jittered.iter().zip(v) {
+                assert!(j >= v / 2 && j <= v);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn aws_full_jitter() {
+        let mut backoff = BackoffSequence::aws_full_jitter(Duration::from_millis(100),
+                                                           Duration::from_secs(1));
+        backoff.max_iterations(8);
+        let ceilings = [100, 200, 400, 800, 1000, 1000, 1000, 1000].map(Duration::from_millis);
+        for _ in 0..10 {
+            let v = backoff.iter().collect::<Vec<_>>();
+            assert_eq!(v.len(), 8);
+            assert!(v.iter().zip(&ceilings).all(|(d, c)| d <= c));
+        }
+
+        let seeded = BackoffSequence::aws_full_jitter_seeded(100u64, 1000, 9);
+        assert_eq!(seeded.iter().take(20).collect::<Vec<_>>(),
+                   BackoffSequence::aws_full_jitter_seeded(100u64, 1000, 9)
+                       .iter()
+                       .take(20)
+                       .collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn duration_shorthands() {
+        let v = BackoffSequence::exponential_millis(10, 2.0)
+            .max(Duration::from_millis(30))
+            .max_iterations(3)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(v, vec![10, 20, 30].into_iter().map(Duration::from_millis).collect::<Vec<_>>());
+
+        let mut backoff = BackoffSequence::exponential_secs(1, 3.0);
+        backoff.max_iterations(3);
+        assert_eq!(backoff.iter().last(), Some(Duration::from_secs(9)));
+
+        let mut backoff = BackoffSequence::linear_millis(100, 50);
+        backoff.max_iterations(3);
+        assert_eq!(backoff.iter().last(), Some(Duration::from_millis(200)));
+
+        let mut backoff = BackoffSequence::linear_secs(1, 2);
+        backoff.max_iterations(3);
+        assert_eq!(backoff.iter().last(), Some(Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,114 @@
+// The calculator on its own, with none of the sequence's limits, for building on with the
+// standard iterator adaptors and the ones in `adaptors`.
+
+use core::convert::TryFrom;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use super::{BackoffSequence, Calculator, CalculatorHandle};
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>
+{
+    /// Iterates over just the calculator's values, from iteration 1 on, ignoring everything
+    /// else the sequence has been set up with: there's no `min`, `max`, `max_iterations` or
+    /// jitter, and it only ends if the calculator ends it.  This keeps nothing but the
+    /// iteration count, for when the standard adaptors (`take`, `map`, ...) are all that's
+    /// needed.
+    pub fn raw_iter(&self) -> RawIter<'_, F, B> {
+        RawIter {
+            calculator: CalculatorHandle::Borrowed(&self.calculator),
+            iteration: 0,
+            value: PhantomData,
+        }
+    }
+}
+
+/// The iterator returned by `BackoffSequence::raw_iter`.
+pub struct RawIter<'a, F: 'a, B> {
+    calculator: CalculatorHandle<'a, F>,
+    iteration: u64,
+    value: PhantomData<fn() -> B>,
+}
+
+impl<'a, F, B> Clone for RawIter<'a, F, B> {
+    fn clone(&self) -> Self {
+        RawIter {
+            calculator: self.calculator.clone(),
+            iteration: self.iteration,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<'a, F, B> RawIter<'a, F, B> {
+    /// The iteration that the last value came from, starting at 1, or 0 before the first value.
+    pub fn iteration(&self) -> u64 {
+        self.iteration
+    }
+}
+
+impl<'a, F, B> Iterator for RawIter<'a, F, B>
+    where F: Calculator<B>
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        // past u64::MAX there are no more iterations to ask the calculator about
+        self.iteration = self.iteration.checked_add(1)?;
+        let value = self.calculator.try_calculate(self.iteration);
+        if value.is_none() {
+            self.iteration = u64::MAX;
+        }
+        value
+    }
+
+    // nothing in between depends on anything, so skip straight to the nth value
+    fn nth(&mut self, n: usize) -> Option<B> {
+        let n = u64::try_from(n).unwrap_or(u64::MAX);
+        self.iteration = self.iteration.saturating_add(n);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.calculator.can_end() {
+            (0, None)
+        } else {
+            (usize::MAX, None)
+        }
+    }
+}
+
+// once the calculator's ended things, the iteration is left at u64::MAX so that it stays over
+impl<'a, F, B> FusedIterator for RawIter<'a, F, B>
+    where F: Calculator<B>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_limits() {
+        let f = &|x| 10u64.saturating_pow(x as u32);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.min(500).max(5000).max_iterations(2);
+
+        let v = backoff.raw_iter().take(4).collect::<Vec<_>>();
+        assert_eq!(v, vec![10, 100, 1000, 10000]);
+
+        let mut iter = backoff.raw_iter();
+        assert_eq!(iter.nth(2), Some(1000));
+        assert_eq!(iter.iteration(), 3);
+        assert_eq!(iter.clone().next(), Some(10000));
+    }
+
+    #[test]
+    fn ends_with_the_calculator() {
+        let backoff = BackoffSequence::from_checked(|x| if x < 3 { Some(x) } else { None });
+        let mut iter = backoff.raw_iter();
+        assert_eq!(iter.by_ref().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(iter.next(), None);
+    }
+}
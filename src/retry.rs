@@ -0,0 +1,854 @@
+use std::fmt::Debug;
+use std::iter::Peekable;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use super::events;
+use super::{BackoffSequence, BackoffSequenceIterator, Calculator, Jitter, NoJitter, SaturatingAdd};
+
+/// Everything a retry helper knows once it's done: the final result, how many times the
+/// operation was attempted, and the sum of the backoff values waited between attempts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryOutcome<T, E, B = Duration> {
+    pub result: Result<T, E>,
+    pub attempts: u64,
+    pub total_wait: B,
+}
+
+/// What an `on_retry` hook is told about each retry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryEvent<B = Duration> {
+    /// The number of the attempt that just failed, starting at 1.
+    pub attempt: u64,
+    /// How long the helper is about to wait before the next attempt.
+    pub delay: B,
+    /// The time since the first attempt started.  The `wasm` helpers have no clock to read, so
+    /// there it's the time spent sleeping between attempts.
+    pub elapsed: Duration,
+}
+
+// the hook set by `on_retry`, which clones of the sequence share
+pub(crate) type RetryHook<B> = Arc<Mutex<dyn FnMut(&RetryEvent<B>) + Send>>;
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J> {
+    /// Calls `hook` whenever one of the retry helpers is about to wait after a failed attempt.
+    /// It's meant for metrics, like counting retries, without this crate needing to know about
+    /// any metrics library.  Clones of the sequence share the same hook.
+    pub fn on_retry<H>(&mut self, hook: H) -> &mut Self
+        where H: FnMut(&RetryEvent<B>) + Send + 'static
+    {
+        self.on_retry = Some(Arc::new(Mutex::new(hook)));
+        self
+    }
+
+    /// Calls `hook` with the number of attempts and the last error if a retry helper runs out
+    /// of attempts, to trip a circuit breaker or raise an alert only once everything has failed.
+    /// It isn't called on success, or when an error is returned early because it wasn't worth
+    /// retrying.  Unlike `on_retry` this is for a single call, so the helpers are on the
+    /// `OnGiveUp` returned, and `RetryDriver` has `run_with_giveup` for the same thing.
+    pub fn on_giveup<H>(&self, hook: H) -> OnGiveUp<'_, 'a, F, B, J, H> {
+        OnGiveUp {
+            sequence: self,
+            hook,
+        }
+    }
+}
+
+/// A sequence with an `on_giveup` hook, made by `BackoffSequence::on_giveup`.
+pub struct OnGiveUp<'s, 'a: 's, F: 'a, B: 's, J: 's, H> {
+    pub(crate) sequence: &'s BackoffSequence<'a, F, B, J>,
+    pub(crate) hook: H,
+}
+
+impl<'s, 'a, F, J, H> OnGiveUp<'s, 'a, F, Duration, J, H>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    /// `BackoffSequence::retry`, calling the hook if every attempt fails.
+    pub fn retry<T, E, O>(self, op: O) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              H: FnOnce(u64, &E)
+    {
+        self.retry_verbose(op).result
+    }
+
+    /// `BackoffSequence::retry_counted`, calling the hook if every attempt fails.
+    pub fn retry_counted<T, E, O>(self, op: O) -> (Result<T, E>, u64)
+        where O: FnMut() -> Result<T, E>,
+              H: FnOnce(u64, &E)
+    {
+        let outcome = self.retry_verbose(op);
+        (outcome.result, outcome.attempts)
+    }
+
+    /// `BackoffSequence::retry_verbose`, calling the hook if every attempt fails.
+    pub fn retry_verbose<T, E, O>(self, op: O) -> RetryOutcome<T, E>
+        where O: FnMut() -> Result<T, E>,
+              H: FnOnce(u64, &E)
+    {
+        self.retry_notify_cf_verbose(op, |_| true, |_, _, _| ControlFlow::Continue(()))
+    }
+
+    /// `BackoffSequence::retry_notify`, calling the hook if every attempt fails.
+    pub fn retry_notify<T, E, O, N>(self, op: O, mut notify: N) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              N: FnMut(&E, u64, Duration),
+              H: FnOnce(u64, &E)
+    {
+        self.retry_notify_cf(op, |e, attempt, delay| {
+            notify(e, attempt, delay);
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// `BackoffSequence::retry_notify_cf`, calling the hook if every attempt fails.  Stopping
+    /// with `Break` doesn't count as giving up.
+    pub fn retry_notify_cf<T, E, O, N>(self, op: O, notify: N) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              N: FnMut(&E, u64, Duration) -> ControlFlow<()>,
+              H: FnOnce(u64, &E)
+    {
+        self.retry_notify_cf_verbose(op, |_| true, notify).result
+    }
+
+    /// `BackoffSequence::retry_if`, calling the hook if every attempt fails.  An error that
+    /// `should_retry` turns down doesn't count as giving up.
+    pub fn retry_if<T, E, O, R>(self, op: O, should_retry: R) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              R: Fn(&E) -> bool,
+              H: FnOnce(u64, &E)
+    {
+        self.retry_notify_cf_verbose(op, should_retry, |_, _, _| ControlFlow::Continue(()))
+            .result
+    }
+
+    /// `BackoffSequence::retry_collect_errors`, calling the hook with the last error if every
+    /// attempt fails.
+    pub fn retry_collect_errors<T, E, O>(self, mut op: O) -> Result<T, Vec<E>>
+        where O: FnMut() -> Result<T, E>,
+              H: FnOnce(u64, &E)
+    {
+        // an empty Vec doesn't allocate, so success on the first attempt costs nothing
+        let mut errors = Vec::new();
+        let outcome = OnGiveUp {
+                sequence: self.sequence,
+                hook: ignore,
+            }
+            .retry_verbose(|| op().map_err(|e| errors.push(e)));
+
+        // every error is retried, so failing at all means the sequence ran out
+        let attempts = outcome.attempts;
+        outcome.result.map_err(|()| {
+            if let Some(last) = errors.last() {
+                (self.hook)(attempts, last);
+            }
+            errors
+        })
+    }
+
+    /// `BackoffSequence::retry_until`, calling the hook if every attempt fails, whether that's
+    /// because the sequence ran out or because the deadline passed.
+    pub fn retry_until<T, E, O>(self, deadline: Instant, op: O) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              H: FnOnce(u64, &E)
+    {
+        let seq = self.sequence;
+        let clock = seq.time();
+        // a sleep that would overshoot the deadline is cut short
+        let wait = |delay: Duration| {
+            let remaining = deadline.saturating_duration_since(clock.now());
+            clock.sleep(delay.min(remaining));
+        };
+        let outcome = seq.retry_with(op, |_| clock.now() < deadline, |_, _, _| (), wait);
+
+        // the deadline is the only thing that stops an error being retried
+        if let Err(ref e) = outcome.result {
+            (self.hook)(outcome.attempts, e);
+        }
+        outcome.result
+    }
+
+    // the loop behind the rest, sleeping on the sequence's clock
+    fn retry_notify_cf_verbose<T, E, O, R, N>(self,
+                                              op: O,
+                                              should_retry: R,
+                                              notify: N)
+                                              -> RetryOutcome<T, E>
+        where O: FnMut() -> Result<T, E>,
+              R: FnMut(&E) -> bool,
+              N: FnMut(&E, u64, Duration) -> ControlFlow<()>,
+              H: FnOnce(u64, &E)
+    {
+        let seq = self.sequence;
+        retry_on(seq,
+                 &mut seq.iter().peekable(),
+                 op,
+                 should_retry,
+                 notify,
+                 |delay| seq.time().sleep(delay),
+                 self.hook)
+    }
+}
+
+// the `on_giveup` hook for the helpers that weren't given one
+pub(crate) fn ignore<E>(_: u64, _: &E) {}
+
+// tells the `on_retry` hook, if there is one, about a retry
+pub(crate) fn report<B: Clone>(hook: Option<&RetryHook<B>>,
+                               attempt: u64,
+                               delay: &B,
+                               elapsed: Duration) {
+    if let Some(hook) = hook {
+        let mut hook = hook.lock().unwrap_or_else(PoisonError::into_inner);
+        (*hook)(&RetryEvent {
+            attempt,
+            delay: delay.clone(),
+            elapsed,
+        });
+    }
+}
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone + SaturatingAdd + Default + Debug,
+          J: Jitter<B> + Clone
+{
+    // the loop behind all of the blocking retry helpers; `should_retry` decides whether an error
+    // is worth another attempt, `notify` hears about each failure that's going to be retried,
+    // and `wait` does the actual sleeping, which is usually on the sequence's clock
+    pub(crate) fn retry_with<T, E, O, R, N, W>(&self,
+                                               op: O,
+                                               should_retry: R,
+                                               notify: N,
+                                               wait: W)
+                                               -> RetryOutcome<T, E, B>
+        where O: FnMut() -> Result<T, E>,
+              R: FnMut(&E) -> bool,
+              N: FnMut(&E, u64, B),
+              W: FnMut(B)
+    {
+        let mut notify = notify;
+        retry_on(self,
+                 &mut self.iter().peekable(),
+                 op,
+                 should_retry,
+                 |e, attempt, delay| {
+                     notify(e, attempt, delay);
+                     ControlFlow::Continue(())
+                 },
+                 wait,
+                 |_, _| ())
+    }
+}
+
+// `notify` can stop things early with `Break`, and `giveup` is only called when the sequence
+// runs out, not when `should_retry` turns an error down or `notify` breaks.  The `on_retry`
+// hook and the clock come from `seq`.
+fn retry_on<F, J, I, B, T, E, O, R, N, W, G>(seq: &BackoffSequence<'_, F, B, J>,
+                                             delays: &mut Peekable<I>,
+                                             mut op: O,
+                                             mut should_retry: R,
+                                             mut notify: N,
+                                             mut wait: W,
+                                             giveup: G)
+                                             -> RetryOutcome<T, E, B>
+    where I: Iterator<Item = B>,
+          B: Clone + SaturatingAdd + Default + Debug,
+          O: FnMut() -> Result<T, E>,
+          R: FnMut(&E) -> bool,
+          N: FnMut(&E, u64, B) -> ControlFlow<()>,
+          W: FnMut(B),
+          G: FnOnce(u64, &E)
+{
+    let hook = seq.on_retry.as_ref();
+    let clock = seq.time();
+    let mut attempts = 0;
+    let mut total_wait = B::default();
+    let mut previous = None;
+    let start = clock.now();
+
+    loop {
+        attempts += 1;
+        let attempt = events::Attempt::start(attempts, previous.as_ref());
+        let err = match attempt.run(&mut op) {
+            Ok(v) => {
+                return RetryOutcome {
+                    result: Ok(v),
+                    attempts,
+                    total_wait,
+                }
+            }
+            Err(e) => e,
+        };
+
+        if !should_retry(&err) {
+            events::giving_up(attempts);
+            return RetryOutcome {
+                result: Err(err),
+                attempts,
+                total_wait,
+            };
+        }
+
+        // each attempt is paired with a value from the sequence, so only sleep if there's
+        // another value (and therefore another attempt) coming after this one
+        match (delays.next(), delays.peek()) {
+            (Some(delay), Some(_)) => {
+                if notify(&err, attempts, delay.clone()).is_break() {
+                    events::giving_up(attempts);
+                    return RetryOutcome {
+                        result: Err(err),
+                        attempts,
+                        total_wait,
+                    };
+                }
+                events::retrying(attempts, &delay);
+                report(hook, attempts, &delay, clock.now().saturating_duration_since(start));
+                total_wait = total_wait.saturating_add(&delay);
+                wait(delay.clone());
+                previous = Some(delay);
+            }
+            _ => {
+                events::giving_up(attempts);
+                giveup(attempts, &err);
+                return RetryOutcome {
+                    result: Err(err),
+                    attempts,
+                    total_wait,
+                }
+            }
+        }
+    }
+}
+
+impl<'a, F, J> BackoffSequence<'a, F, Duration, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    /// Calls `op` until it succeeds or the sequence runs out, sleeping for each backoff value
+    /// between attempts.  `max_iterations` caps the number of attempts, but `op` is always
+    /// called at least once.
+    pub fn retry<T, E, O>(&self, op: O) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.on_giveup(ignore).retry(op)
+    }
+
+    /// Like `retry`, but also returns the number of attempts that were made.
+    pub fn retry_counted<T, E, O>(&self, op: O) -> (Result<T, E>, u64)
+        where O: FnMut() -> Result<T, E>
+    {
+        self.on_giveup(ignore).retry_counted(op)
+    }
+
+    /// Like `retry`, but reports the number of attempts and the total time spent sleeping.
+    pub fn retry_verbose<T, E, O>(&self, op: O) -> RetryOutcome<T, E>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.on_giveup(ignore).retry_verbose(op)
+    }
+
+    /// Like `retry`, but calls `notify` with the error, the attempt number, and the delay about
+    /// to be slept after each failed attempt.  It isn't called after the final attempt.
+    pub fn retry_notify<T, E, O, N>(&self, op: O, notify: N) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              N: FnMut(&E, u64, Duration)
+    {
+        self.on_giveup(ignore).retry_notify(op, notify)
+    }
+
+    /// Like `retry_notify`, but `notify` can return `Break` to stop retrying, like when the
+    /// process is shutting down.  The error from that attempt is returned straight away,
+    /// without sleeping.
+    pub fn retry_notify_cf<T, E, O, N>(&self, op: O, notify: N) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              N: FnMut(&E, u64, Duration) -> ControlFlow<()>
+    {
+        self.on_giveup(ignore).retry_notify_cf(op, notify)
+    }
+
+    /// Like `retry`, but only retries errors for which `should_retry` returns true.  Any other
+    /// error is returned straight away, without sleeping.
+    pub fn retry_if<T, E, O, R>(&self, op: O, should_retry: R) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              R: Fn(&E) -> bool
+    {
+        self.on_giveup(ignore).retry_if(op, should_retry)
+    }
+
+    /// Like `retry`, but gives back every error in the order they happened, rather than just the
+    /// last one.
+    pub fn retry_collect_errors<T, E, O>(&self, op: O) -> Result<T, Vec<E>>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.on_giveup(ignore).retry_collect_errors(op)
+    }
+
+    /// Like `retry`, but stops once `deadline` has passed, even if the sequence hasn't run out.
+    /// A sleep that would overshoot the deadline is cut short, so that the final attempt happens
+    /// right at the deadline.
+    pub fn retry_until<T, E, O>(&self, deadline: Instant, op: O) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.on_giveup(ignore).retry_until(deadline, op)
+    }
+}
+
+/// Retries an operation over and over again, for long running workers that fail, recover, and
+/// then fail again later on.  The backoff goes back to the start of the sequence after a
+/// success, and after giving up, so that each `run` gets the full number of attempts.
+pub struct RetryDriver<'a, F: 'a, B, J = NoJitter>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    sequence: &'a BackoffSequence<'a, F, B, J>,
+    delays: Peekable<BackoffSequenceIterator<'a, F, B, J>>,
+}
+
+impl<'a, F, B, J> RetryDriver<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
+{
+    pub fn new(sequence: &'a BackoffSequence<'a, F, B, J>) -> Self {
+        RetryDriver {
+            sequence,
+            delays: sequence.iter().peekable(),
+        }
+    }
+
+    /// Starts the backoff over from the beginning of the sequence.
+    pub fn reset(&mut self) {
+        self.delays = self.sequence.iter().peekable();
+    }
+}
+
+impl<'a, F, J> RetryDriver<'a, F, Duration, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    /// Retries `op` in the same way as `BackoffSequence::retry`, resetting the backoff if it
+    /// succeeds.
+    pub fn run<T, E, O>(&mut self, op: O) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.run_with_giveup(op, ignore)
+    }
+
+    /// Like `run`, but calls `hook` with the number of attempts and the last error if every
+    /// attempt fails, like `BackoffSequence::on_giveup` does.
+    pub fn run_with_giveup<T, E, O, H>(&mut self, op: O, hook: H) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>,
+              H: FnOnce(u64, &E)
+    {
+        // a run that gave up used the sequence up, so this one starts over
+        if self.delays.peek().is_none() {
+            self.reset();
+        }
+        let clock = self.sequence.time();
+        let result = retry_on(self.sequence,
+                              &mut self.delays,
+                              op,
+                              |_| true,
+                              |_, _, _| ControlFlow::Continue(()),
+                              |delay| clock.sleep(delay),
+                              hook)
+            .result;
+        if result.is_ok() {
+            self.reset();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, MockClock};
+    use std::cell::Cell;
+    use std::time::Instant;
+
+    #[test]
+    fn succeeds_first_time() {
+        let f = &|_| Duration::from_secs(10);
+        let backoff = BackoffSequence::new(f);
+
+        let start = Instant::now();
+        let (res, attempts) = backoff.retry_counted(|| Ok::<_, ()>(5));
+        assert_eq!(res, Ok(5));
+        assert_eq!(attempts, 1);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn succeeds_eventually() {
+        let f = &|i| Duration::from_millis(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(10);
+
+        let mut calls = 0;
+        let (res, attempts) = backoff.retry_counted(|| {
+            calls += 1;
+            if calls < 4 { Err(calls) } else { Ok(calls) }
+        });
+        assert_eq!(res, Ok(4));
+        assert_eq!(attempts, 4);
+    }
+
+    #[test]
+    fn gives_up_with_last_error() {
+        let f = &|i| Duration::from_millis(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let mut calls = 0;
+        let (res, attempts) = backoff.retry_counted(|| {
+            calls += 1;
+            Err::<(), _>(calls)
+        });
+        assert_eq!(res, Err(3));
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn no_sleep_after_last_attempt() {
+        let f = &|i| Duration::from_millis(if i == 1 { 1 } else { 10_000 });
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(2);
+
+        let start = Instant::now();
+        assert_eq!(backoff.retry(|| Err::<(), _>("nope")), Err("nope"));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn zero_iterations_still_attempts_once() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(0);
+
+        assert_eq!(backoff.retry_counted(|| Err::<(), _>(())), (Err(()), 1));
+    }
+
+    #[test]
+    fn verbose_outcome() {
+        let f = &|i| Duration::from_millis(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(4);
+
+        let mut calls = 0;
+        let outcome = backoff.retry_verbose(|| {
+            calls += 1;
+            if calls < 3 { Err(calls) } else { Ok(calls) }
+        });
+        assert_eq!(outcome,
+                   RetryOutcome {
+                       result: Ok(3),
+                       attempts: 3,
+                       total_wait: Duration::from_millis(3),
+                   });
+    }
+
+    #[test]
+    fn verbose_outcome_generic_wait() {
+        let f = &|i| i * 10;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let mut waited = vec![];
+        let outcome = backoff.retry_with(|| Err::<(), _>("nope"),
+                                         |_| true,
+                                         |_, _, _| (),
+                                         |d| waited.push(d));
+        assert_eq!(outcome.result, Err("nope"));
+        assert_eq!(outcome.attempts, 3);
+        assert_eq!(outcome.total_wait, 30);
+        assert_eq!(waited, vec![10, 20]);
+    }
+
+    #[test]
+    fn total_wait_saturates() {
+        let f = &|_| Duration::MAX;
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let outcome = backoff.retry_with(|| Err::<(), _>(()), |_| true, |_, _, _| (), |_| ());
+        assert_eq!(outcome.total_wait, Duration::MAX);
+    }
+
+    #[test]
+    fn notify_before_each_retry() {
+        let f = &|i| Duration::from_millis(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let mut notes = vec![];
+        let res = backoff.retry_notify(|| Err::<(), _>("timeout"),
+                                       |e, attempt, delay| notes.push((*e, attempt, delay)));
+        assert_eq!(res, Err("timeout"));
+        assert_eq!(notes,
+                   vec![("timeout", 1, Duration::from_millis(1)),
+                        ("timeout", 2, Duration::from_millis(2))]);
+    }
+
+    #[test]
+    fn notify_can_break() {
+        let clock = MockClock::new();
+        let f = &|_| Duration::from_millis(10);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(10).clock(clock.clone());
+
+        let mut calls = 0;
+        let op = || {
+            calls += 1;
+            Err::<(), _>(calls)
+        };
+        let res = backoff.retry_notify_cf(op, |_, attempt, _| {
+            if attempt == 3 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!(res, Err(3));
+        // slept after the first two attempts, but not the third
+        assert_eq!(clock.elapsed(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn hook_on_each_retry() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let seen = events.clone();
+        let f = &|i| Duration::from_millis(i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3).on_retry(move |e| seen.lock().unwrap().push(e.clone()));
+
+        assert_eq!(backoff.clone().retry(|| Err::<(), _>("nope")), Err("nope"));
+        let events = events.lock().unwrap();
+        assert_eq!(events.iter().map(|e| (e.attempt, e.delay)).collect::<Vec<_>>(),
+                   vec![(1, Duration::from_millis(1)), (2, Duration::from_millis(2))]);
+        assert!(events[1].elapsed >= Duration::from_millis(1));
+    }
+
+    #[test]
+    fn giveup_only_when_exhausted() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let mut gave_up = vec![];
+        let res = backoff.on_giveup(|n, e: &String| gave_up.push((n, e.clone())))
+            .retry(|| Err::<(), _>("x".to_string()));
+        assert_eq!(res, Err("x".to_string()));
+        assert_eq!(gave_up, vec![(3, "x".to_string())]);
+
+        let mut calls = 0;
+        let res = backoff.on_giveup(|_, _: &u64| panic!("gave up")).retry(|| {
+            calls += 1;
+            if calls < 3 { Err(calls) } else { Ok(calls) }
+        });
+        assert_eq!(res, Ok(3));
+
+        let res = backoff.on_giveup(|_, _: &&str| panic!("gave up"))
+            .retry_if(|| Err::<(), _>("fatal"), |e| *e != "fatal");
+        assert_eq!(res, Err("fatal"));
+    }
+
+    #[test]
+    fn every_helper_gives_up_once() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let gave_up = Cell::new(vec![]);
+        let hook = |n, e: &u64| {
+            let mut seen = gave_up.take();
+            seen.push((n, *e));
+            gave_up.set(seen);
+        };
+        let calls = Cell::new(0);
+        let fail = || {
+            calls.set(calls.get() + 1);
+            Err::<(), _>(calls.get())
+        };
+
+        assert_eq!(backoff.on_giveup(hook).retry(&fail), Err(3));
+        calls.set(0);
+        assert_eq!(backoff.on_giveup(hook).retry_counted(&fail), (Err(3), 3));
+        calls.set(0);
+        assert_eq!(backoff.on_giveup(hook).retry_verbose(&fail).result, Err(3));
+        calls.set(0);
+        assert_eq!(backoff.on_giveup(hook).retry_notify(&fail, |_, _, _| ()), Err(3));
+        calls.set(0);
+        assert_eq!(backoff.on_giveup(hook)
+                       .retry_notify_cf(&fail, |_, _, _| ControlFlow::Continue(())),
+                   Err(3));
+        calls.set(0);
+        assert_eq!(backoff.on_giveup(hook).retry_if(&fail, |_| true), Err(3));
+        calls.set(0);
+        assert_eq!(backoff.on_giveup(hook).retry_collect_errors(&fail), Err(vec![1, 2, 3]));
+        calls.set(0);
+        let far_off = Instant::now() + Duration::from_secs(60);
+        assert_eq!(backoff.on_giveup(hook).retry_until(far_off, &fail), Err(3));
+        calls.set(0);
+        assert_eq!(RetryDriver::new(&backoff).run_with_giveup(&fail, hook), Err(3));
+
+        assert_eq!(gave_up.take(), vec![(3, 3); 9]);
+    }
+
+    #[test]
+    fn giveup_at_the_deadline() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let mut gave_up = vec![];
+        let res = backoff.on_giveup(|n, e: &String| gave_up.push((n, e.clone())))
+            .retry_until(Instant::now(), || Err::<(), _>("late".to_string()));
+        assert_eq!(res, Err("late".to_string()));
+        assert_eq!(gave_up, vec![(1, "late".to_string())]);
+    }
+
+    #[test]
+    fn no_giveup_on_success() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let hook = |_: u64, _: &u64| panic!("gave up");
+        let mut calls = 0;
+        let mut flaky = || {
+            calls += 1;
+            if calls % 2 == 1 { Err(calls) } else { Ok(calls) }
+        };
+
+        assert_eq!(backoff.on_giveup(hook).retry(&mut flaky), Ok(2));
+        assert_eq!(backoff.on_giveup(hook).retry_counted(&mut flaky), (Ok(4), 2));
+        assert_eq!(backoff.on_giveup(hook).retry_verbose(&mut flaky).result, Ok(6));
+        assert_eq!(backoff.on_giveup(hook).retry_notify(&mut flaky, |_, _, _| ()), Ok(8));
+        assert_eq!(backoff.on_giveup(hook)
+                       .retry_notify_cf(&mut flaky, |_, _, _| ControlFlow::Break(())),
+                   Err(9));
+        assert_eq!(backoff.on_giveup(hook).retry_if(&mut flaky, |_| true), Ok(10));
+        assert_eq!(backoff.on_giveup(hook).retry_collect_errors(&mut flaky), Ok(12));
+        let far_off = Instant::now() + Duration::from_secs(60);
+        assert_eq!(backoff.on_giveup(hook).retry_until(far_off, &mut flaky), Ok(14));
+        assert_eq!(RetryDriver::new(&backoff).run_with_giveup(&mut flaky, hook), Ok(16));
+    }
+
+    #[test]
+    fn collects_every_error() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let mut calls = 0;
+        let res = backoff.retry_collect_errors(|| {
+            calls += 1;
+            Err::<(), _>(calls)
+        });
+        assert_eq!(res, Err(vec![1, 2, 3]));
+
+        let mut calls = 0;
+        let res = backoff.retry_collect_errors(|| {
+            calls += 1;
+            if calls < 2 { Err(calls) } else { Ok(calls) }
+        });
+        assert_eq!(res, Ok(2));
+    }
+
+    #[test]
+    fn retry_until_deadline() {
+        let f = &|_| Duration::from_millis(30);
+        let backoff = BackoffSequence::new(f);
+
+        let start = Instant::now();
+        let mut calls = 0;
+        let res = backoff.retry_until(start + Duration::from_millis(50), || {
+            calls += 1;
+            Err::<(), _>(calls)
+        });
+        // attempts at 0ms and 30ms, then a shortened sleep puts the last one at the deadline
+        assert_eq!(res, Err(3));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn deadline_on_mock_clock() {
+        let clock = MockClock::new();
+        let f = &|_| Duration::from_secs(30);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.clock(clock.clone());
+
+        let deadline = clock.now() + Duration::from_secs(100);
+        let mut calls = vec![];
+        let res = backoff.retry_until(deadline, || {
+            calls.push(clock.elapsed());
+            Err::<(), _>(())
+        });
+        assert_eq!(res, Err(()));
+        let secs = |s: &[u64]| s.iter().map(|&s| Duration::from_secs(s)).collect::<Vec<_>>();
+        assert_eq!(calls, secs(&[0, 30, 60, 90, 100]));
+    }
+
+    #[test]
+    fn retry_until_succeeds() {
+        let f = &|_| Duration::from_millis(1);
+        let backoff = BackoffSequence::new(f);
+
+        let mut calls = 0;
+        let res = backoff.retry_until(Instant::now() + Duration::from_secs(5), || {
+            calls += 1;
+            if calls < 3 { Err(calls) } else { Ok(calls) }
+        });
+        assert_eq!(res, Ok(3));
+    }
+
+    #[test]
+    fn retry_if_fails_fast() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(10);
+
+        let mut calls = 0;
+        let res = backoff.retry_if(|| {
+                                       calls += 1;
+                                       Err::<(), _>(if calls == 2 { "fatal" } else { "transient" })
+                                   },
+                                   |e| *e == "transient");
+        assert_eq!(res, Err("fatal"));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn driver_resets_after_each_cycle() {
+        let f = &|_| Duration::from_millis(1);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+        let mut driver = RetryDriver::new(&backoff);
+
+        let mut calls = 0;
+        assert_eq!(driver.run(|| {
+                       calls += 1;
+                       Err::<(), _>(())
+                   }),
+                   Err(()));
+        assert_eq!(calls, 3);
+
+        // giving up started things over, so this cycle gets every attempt again
+        calls = 0;
+        assert_eq!(driver.run(|| {
+                       calls += 1;
+                       if calls < 3 { Err(calls) } else { Ok(calls) }
+                   }),
+                   Ok(3));
+
+        // the success started things over
+        calls = 0;
+        assert_eq!(driver.run(|| {
+                       calls += 1;
+                       Err::<(), _>(())
+                   }),
+                   Err(()));
+        assert_eq!(calls, 3);
+    }
+}
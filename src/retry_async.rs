@@ -0,0 +1,171 @@
+// The runtime-independent half of the async retry helpers.  Each runtime module supplies its
+// own sleep future, so enabling several runtimes at once doesn't make them step on each other.
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::events;
+use crate::retry::{self, RetryHook};
+use crate::{BackoffSequence, BackoffSequenceIterator, Calculator, Clock, Jitter, OnGiveUp};
+
+impl<'s, 'a, F, J, H> OnGiveUp<'s, 'a, F, Duration, J, H>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    /// The async version of `retry`, for any runtime: `sleep` is the runtime's sleep function,
+    /// like `tokio::time::sleep`.  The hook is called if every attempt fails.
+    pub async fn retry_async<T, E, Fut, O, S, SFut>(self, op: O, sleep: S) -> Result<T, E>
+        where O: FnMut() -> Fut,
+              Fut: Future<Output = Result<T, E>>,
+              S: FnMut(Duration) -> SFut,
+              SFut: Future<Output = ()>,
+              H: FnOnce(u64, &E)
+    {
+        // `Instant::now` panics on wasm32-unknown-unknown
+        let clock = if cfg!(target_arch = "wasm32") { None } else { Some(self.sequence.time()) };
+        retry(self.sequence, clock, op, sleep, self.hook).await
+    }
+}
+
+// `clock` is the sequence's, for the `elapsed` that `on_retry` is told about; the sleeping is
+// still done by `sleep`.  `Instant::now` panics on wasm32-unknown-unknown, so without a clock
+// this counts the time slept so far instead.  `giveup` is only called when the sequence runs
+// out.
+pub(crate) async fn retry<'a, F, J, T, E, Fut, O, S, SFut, G>(
+    seq: &BackoffSequence<'a, F, Duration, J>,
+    clock: Option<&(dyn Clock + Send + Sync)>,
+    op: O,
+    sleep: S,
+    giveup: G)
+    -> Result<T, E>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone,
+          O: FnMut() -> Fut,
+          Fut: Future<Output = Result<T, E>>,
+          S: FnMut(Duration) -> SFut,
+          SFut: Future<Output = ()>,
+          G: FnOnce(u64, &E)
+{
+    let giveup = |attempts, result: &Result<T, E>| {
+        if let Err(e) = result {
+            giveup(attempts, e);
+        }
+    };
+    retry_results(seq.iter(), seq.on_retry.as_ref(), clock, op, Result::is_err, sleep, giveup)
+        .await
+}
+
+// `retry`, for callers like the tower service that can retry an `Ok` as well: `should_retry`
+// is asked about each result, and `giveup` gets whichever one the sequence ran out on.  This
+// takes the delays and the `on_retry` hook rather than the sequence, so that the future doesn't
+// need the sequence to be `Sync` to be `Send`.
+pub(crate) async fn retry_results<'a, F, J, T, E, Fut, O, R, S, SFut, G>(
+    delays: BackoffSequenceIterator<'a, F, Duration, J>,
+    hook: Option<&RetryHook<Duration>>,
+    clock: Option<&(dyn Clock + Send + Sync)>,
+    mut op: O,
+    mut should_retry: R,
+    mut sleep: S,
+    giveup: G)
+    -> Result<T, E>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone,
+          O: FnMut() -> Fut,
+          Fut: Future<Output = Result<T, E>>,
+          R: FnMut(&Result<T, E>) -> bool,
+          S: FnMut(Duration) -> SFut,
+          SFut: Future<Output = ()>,
+          G: FnOnce(u64, &Result<T, E>)
+{
+    let mut delays = delays.peekable();
+    let mut attempts = 0;
+    let mut previous = None;
+    let mut slept = Duration::ZERO;
+    let start = clock.map(|clock| (clock, clock.now()));
+
+    loop {
+        attempts += 1;
+        // scoped so that the result isn't held across the sleep, which would need it to be
+        // `Send` for the future to be
+        let delay = {
+            let attempt = events::Attempt::start(attempts, previous.as_ref());
+            let result = attempt.run_async(op()).await;
+            if !should_retry(&result) {
+                if result.is_err() {
+                    events::giving_up(attempts);
+                }
+                return result;
+            }
+
+            match (delays.next(), delays.peek()) {
+                (Some(delay), Some(_)) => delay,
+                _ => {
+                    events::giving_up(attempts);
+                    giveup(attempts, &result);
+                    return result;
+                }
+            }
+        };
+
+        events::retrying(attempts, &delay);
+        let elapsed = start.map_or(slept, |(clock, start)| {
+            clock.now().saturating_duration_since(start)
+        });
+        retry::report(hook, attempts, &delay, elapsed);
+        sleep(delay).await;
+        slept = slept.saturating_add(delay);
+        previous = Some(delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[::tokio::test]
+    async fn elapsed_without_a_start_is_the_time_slept() {
+        let mut backoff = BackoffSequence::linear_millis(10, 10);
+        backoff.max_iterations(4);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook = seen.clone();
+        backoff.on_retry(move |event| hook.lock().unwrap().push(event.elapsed));
+
+        let res = retry(&backoff, None, || async { Err::<(), _>(()) }, |_| async {}, retry::ignore)
+            .await;
+
+        assert_eq!(res, Err(()));
+        let millis: Vec<_> = seen.lock().unwrap().iter().map(Duration::as_millis).collect();
+        assert_eq!(millis, vec![0, 10, 30]);
+    }
+
+    #[::tokio::test]
+    async fn gives_up_once() {
+        let mut backoff = BackoffSequence::linear_millis(10, 10);
+        backoff.max_iterations(3);
+
+        let mut gave_up = vec![];
+        let mut calls = 0;
+        let res = backoff.on_giveup(|n, e: &u64| gave_up.push((n, *e)))
+            .retry_async(|| {
+                             calls += 1;
+                             let c = calls;
+                             async move { Err::<(), _>(c) }
+                         },
+                         |_| async {})
+            .await;
+        assert_eq!(res, Err(3));
+        assert_eq!(gave_up, vec![(3, 3)]);
+
+        let mut calls = 0;
+        let res = backoff.on_giveup(|_, _: &u64| panic!("gave up"))
+            .retry_async(|| {
+                             calls += 1;
+                             let c = calls;
+                             async move { if c < 3 { Err(c) } else { Ok(c) } }
+                         },
+                         |_| async {})
+            .await;
+        assert_eq!(res, Ok(3));
+    }
+}
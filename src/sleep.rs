@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use super::{BackoffSequence, BackoffSequenceIterator, Calculator, Clock, Jitter, NoJitter};
+
+impl<'a, F, J> BackoffSequence<'a, F, Duration, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    /// An iterator that blocks the current thread for each backoff value before yielding it, so
+    /// that the body of `for _ in seq.sleep_iter() { ... }` is already spaced out.
+    pub fn sleep_iter(&self) -> BlockingSleepIter<'_, F, J> {
+        BlockingSleepIter {
+            delays: self.iter(),
+            clock: self.time(),
+        }
+    }
+}
+
+/// The iterator returned by `BackoffSequence::sleep_iter`.  Every call to `next()` sleeps on
+/// the sequence's clock, which is `thread::sleep` unless it's been changed.
+pub struct BlockingSleepIter<'a, F: 'a, J = NoJitter> {
+    delays: BackoffSequenceIterator<'a, F, Duration, J>,
+    clock: &'a (dyn Clock + Send + Sync),
+}
+
+impl<'a, F, J> Iterator for BlockingSleepIter<'a, F, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration>
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let delay = self.delays.next()?;
+        self.clock.sleep(delay);
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockClock;
+    use std::time::Instant;
+
+    #[test]
+    fn sleeps_before_each_value() {
+        let f = &|i| Duration::from_millis(5 * i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let start = Instant::now();
+        let mut slept = Duration::from_millis(0);
+        for delay in backoff.sleep_iter() {
+            slept += delay;
+            assert!(start.elapsed() >= slept);
+        }
+        assert_eq!(slept, Duration::from_millis(30));
+    }
+
+    #[test]
+    fn sleeps_on_mock_clock() {
+        let clock = MockClock::new();
+        let f = &|_| Duration::from_secs(3600);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(24).clock(clock.clone());
+        assert_eq!(backoff.sleep_iter().count(), 24);
+        assert_eq!(clock.elapsed(), Duration::from_secs(24 * 3600));
+    }
+
+    #[test]
+    fn empty_sequence_never_sleeps() {
+        let f = &|_| Duration::from_secs(3600);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(0);
+
+        assert_eq!(backoff.sleep_iter().next(), None);
+    }
+}
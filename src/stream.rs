@@ -0,0 +1,66 @@
+// A `Stream` of the values themselves, enabled with the `stream` feature.  Nothing here sleeps,
+// so it works with whichever runtime and timer the caller likes.
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::{BackoffSequence, BackoffSequenceIterator, Calculator, Jitter, NoJitter};
+
+impl<'a, F, B, J> BackoffSequence<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B> + Clone
+{
+    /// The values of the sequence as a stream, which is always ready with the next one.  Unlike
+    /// the runtime specific streams it doesn't sleep, so pair each value with a timer of your
+    /// own.
+    pub fn delays_stream(&self) -> DelayStream<'_, F, B, J> {
+        DelayStream { delays: self.iter() }
+    }
+}
+
+/// The stream returned by `BackoffSequence::delays_stream`.
+pub struct DelayStream<'a, F: 'a, B, J = NoJitter> {
+    delays: BackoffSequenceIterator<'a, F, B, J>,
+}
+
+// nothing is ever pinned in place, the iterator just gets polled through `&mut`
+impl<'a, F, B, J> Unpin for DelayStream<'a, F, B, J> {}
+
+impl<'a, F, B, J> Stream for DelayStream<'a, F, B, J>
+    where F: Calculator<B>,
+          B: PartialOrd + Clone,
+          J: Jitter<B>
+{
+    type Item = B;
+
+    fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<B>> {
+        Poll::Ready(self.get_mut().delays.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.delays.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio_stream::StreamExt;
+
+    #[::tokio::test]
+    async fn yields_each_value() {
+        let backoff = BackoffSequence::linear_millis(10, 5);
+        let mut stream = backoff.delays_stream().take(3);
+        assert_eq!(stream.size_hint(), (3, Some(3)));
+
+        let mut total = Duration::ZERO;
+        while let Some(delay) = stream.next().await {
+            total += delay;
+        }
+        assert_eq!(total, Duration::from_millis(45));
+    }
+}
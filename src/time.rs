@@ -0,0 +1,121 @@
+// `time::Duration` support, enabled with the `time` feature.  Like the `chrono` support, the
+// arithmetic traits let the presets and jitter work with it, and the retry helpers turn each
+// value into a `std::time::Duration` to sleep for.
+
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use ::time::Duration as TimeDuration;
+
+use crate::{BackoffSequence, Bounded, Calculator, Exponential, Halve, Jitter, RetryOutcome,
+            SaturatingAdd, SaturatingMul, Scale};
+
+impl Halve for TimeDuration {
+    fn halve(&self) -> Self {
+        *self / 2
+    }
+}
+
+impl SaturatingAdd for TimeDuration {
+    fn saturating_add(&self, rhs: &Self) -> Self {
+        TimeDuration::saturating_add(*self, *rhs)
+    }
+}
+
+impl SaturatingMul for TimeDuration {
+    fn saturating_mul(&self, rhs: u64) -> Self {
+        match i32::try_from(rhs) {
+            Ok(rhs) => TimeDuration::saturating_mul(*self, rhs),
+            Err(_) if self.is_zero() => TimeDuration::ZERO,
+            Err(_) if self.is_positive() => TimeDuration::MAX,
+            Err(_) => TimeDuration::MIN,
+        }
+    }
+}
+
+impl Scale for TimeDuration {
+    fn scale(&self, factor: f64) -> Self {
+        TimeDuration::saturating_seconds_f64(self.as_seconds_f64() * factor)
+    }
+}
+
+impl Bounded for TimeDuration {
+    fn max_value() -> Self {
+        TimeDuration::MAX
+    }
+}
+
+impl BackoffSequence<'static, Exponential<TimeDuration>, TimeDuration> {
+    /// `exponential` for `time::Duration`.  A negative `initial` makes every value negative,
+    /// which the retry helpers sleep for as zero.
+    pub fn exponential_time(initial: TimeDuration, factor: f64) -> Self {
+        BackoffSequence::exponential(initial, factor)
+    }
+}
+
+// how long to really sleep for; `time` durations can be negative, but sleeps can't
+fn to_std(delay: TimeDuration) -> Duration {
+    Duration::try_from(delay).unwrap_or(Duration::ZERO)
+}
+
+impl<'a, F, J> BackoffSequence<'a, F, TimeDuration, J>
+    where F: Calculator<TimeDuration>,
+          J: Jitter<TimeDuration> + Clone
+{
+    /// `BackoffSequence::retry` for `time::Duration`, sleeping for each value between attempts.
+    /// Negative values don't sleep at all.
+    pub fn retry<T, E, O>(&self, op: O) -> Result<T, E>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.retry_verbose(op).result
+    }
+
+    /// Like `retry`, but reports the number of attempts and the total of the values waited.
+    pub fn retry_verbose<T, E, O>(&self, op: O) -> RetryOutcome<T, E, TimeDuration>
+        where O: FnMut() -> Result<T, E>
+    {
+        self.retry_with(op, |_| true, |_, _, _| (), |delay| self.time().sleep(to_std(delay)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockClock;
+
+    #[test]
+    fn arithmetic() {
+        let second = TimeDuration::SECOND;
+        assert_eq!(second.halve(), TimeDuration::milliseconds(500));
+        assert_eq!(second.scale(1.5), TimeDuration::milliseconds(1500));
+        assert_eq!(second.scale(-2.0), TimeDuration::seconds(-2));
+        assert_eq!(TimeDuration::MAX.scale(2.0), TimeDuration::MAX);
+        assert_eq!(SaturatingAdd::saturating_add(&TimeDuration::MAX, &second), TimeDuration::MAX);
+        assert_eq!(SaturatingMul::saturating_mul(&second, u64::MAX), TimeDuration::MAX);
+        assert_eq!(SaturatingMul::saturating_mul(&-second, u64::MAX), TimeDuration::MIN);
+    }
+
+    #[test]
+    fn exponential_preset() {
+        let mut backoff = BackoffSequence::exponential_time(TimeDuration::milliseconds(100), 2.0);
+        backoff.max(TimeDuration::milliseconds(300));
+        assert_eq!(backoff.iter().take(3).collect::<Vec<_>>(),
+                   vec![TimeDuration::milliseconds(100),
+                        TimeDuration::milliseconds(200),
+                        TimeDuration::milliseconds(300)]);
+    }
+
+    #[test]
+    fn retry_sleeps_negative_as_zero() {
+        let clock = MockClock::new();
+        let f = &|i| TimeDuration::seconds(2 - i as i64);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(4).clock(clock.clone());
+
+        let outcome = backoff.retry_verbose(|| Err::<(), _>(()));
+        assert_eq!(outcome.attempts, 4);
+        assert_eq!(outcome.total_wait, TimeDuration::ZERO);
+        // 1s, then 0s, then -1s which sleeps for nothing
+        assert_eq!(clock.elapsed(), Duration::from_secs(1));
+    }
+}
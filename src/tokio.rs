@@ -0,0 +1,194 @@
+// Tokio integration, enabled with the `tokio` feature.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use ::tokio::time::{sleep, Sleep};
+use futures_core::Stream;
+
+use crate::{retry, retry_async};
+use crate::{BackoffSequence, BackoffSequenceIterator, Calculator, Jitter, NoJitter};
+
+/// Async retries that wait using `tokio::time::sleep`.
+pub trait RetryAsync {
+    /// The async version of `BackoffSequence::retry`: awaits `op` until it succeeds or the
+    /// sequence runs out, sleeping on the Tokio timer between attempts.  Dropping the returned
+    /// future cancels any pending sleep along with it.
+    fn retry_async<T, E, Fut, O>(&self, op: O) -> impl Future<Output = Result<T, E>>
+        where O: FnMut() -> Fut,
+              Fut: Future<Output = Result<T, E>>;
+}
+
+impl<'a, F, J> RetryAsync for BackoffSequence<'a, F, Duration, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    async fn retry_async<T, E, Fut, O>(&self, op: O) -> Result<T, E>
+        where O: FnMut() -> Fut,
+              Fut: Future<Output = Result<T, E>>
+    {
+        retry_async::retry(self, Some(self.time()), op, sleep, retry::ignore).await
+    }
+}
+
+impl<'a, F, J> BackoffSequence<'a, F, Duration, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    /// A stream that sleeps for each backoff value in turn, yielding once after each sleep, and
+    /// ending when the sequence does.
+    pub fn into_stream(&self) -> BackoffStream<'_, F, J> {
+        BackoffStream {
+            delays: self.iter(),
+            sleep: None,
+        }
+    }
+}
+
+/// The stream returned by `BackoffSequence::into_stream`.
+pub struct BackoffStream<'a, F: 'a, J = NoJitter> {
+    delays: BackoffSequenceIterator<'a, F, Duration, J>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<'a, F, J> Stream for BackoffStream<'a, F, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Unpin
+{
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        let this = self.get_mut();
+
+        if this.sleep.is_none() {
+            match this.delays.next() {
+                Some(delay) => this.sleep = Some(Box::pin(sleep(delay))),
+                None => return Poll::Ready(None),
+            }
+        }
+
+        match this.sleep.as_mut().map(|s| s.as_mut().poll(cx)) {
+            Some(Poll::Ready(())) => {
+                this.sleep = None;
+                Poll::Ready(Some(()))
+            }
+            _ => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockClock;
+    use ::tokio::time::{timeout, Instant};
+    use std::sync::{Arc, Mutex};
+    use tokio_stream::StreamExt;
+
+    #[::tokio::test(start_paused = true)]
+    async fn retries_until_success() {
+        let f = &|i| Duration::from_millis(10 * i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(5);
+
+        let start = Instant::now();
+        let mut calls = 0;
+        let res = backoff.retry_async(|| {
+                calls += 1;
+                let c = calls;
+                async move { if c < 3 { Err(c) } else { Ok(c) } }
+            })
+            .await;
+
+        assert_eq!(res, Ok(3));
+        // slept 10ms then 20ms
+        assert_eq!(start.elapsed(), Duration::from_millis(30));
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn gives_up_with_last_error() {
+        let f = &|i| Duration::from_millis(10 * i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let start = Instant::now();
+        let mut calls = 0;
+        let res = backoff.retry_async(|| {
+                calls += 1;
+                let c = calls;
+                async move { Err::<(), _>(c) }
+            })
+            .await;
+
+        assert_eq!(res, Err(3));
+        assert_eq!(start.elapsed(), Duration::from_millis(30));
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn elapsed_comes_from_the_clock() {
+        let f = &|i| Duration::from_millis(10 * i);
+        let mut backoff = BackoffSequence::new(f);
+        let clock = MockClock::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook = seen.clone();
+        backoff.max_iterations(3)
+            .clock(clock.clone())
+            .on_retry(move |event| hook.lock().unwrap().push(event.elapsed));
+
+        // only the attempts move the mock clock on; the sleeps are the Tokio timer's
+        let res = backoff.retry_async(|| {
+                clock.advance(Duration::from_millis(5));
+                async { Err::<(), _>(()) }
+            })
+            .await;
+
+        assert_eq!(res, Err(()));
+        assert_eq!(*seen.lock().unwrap(),
+                   vec![Duration::from_millis(5), Duration::from_millis(10)]);
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn dropping_cancels_sleep() {
+        let f = &|_| Duration::from_secs(3600);
+        let backoff = BackoffSequence::new(f);
+
+        let mut calls = 0;
+        let res = timeout(Duration::from_secs(1),
+                          backoff.retry_async(|| {
+                              calls += 1;
+                              async { Err::<(), _>(()) }
+                          }))
+            .await;
+
+        assert!(res.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn stream_sleeps_each_step() {
+        let f = &|i| Duration::from_millis(10 * i);
+        let mut backoff = BackoffSequence::new(f);
+        backoff.max_iterations(3);
+
+        let start = Instant::now();
+        let mut stream = backoff.into_stream();
+        let mut steps = 0;
+        while stream.next().await.is_some() {
+            steps += 1;
+            assert_eq!(start.elapsed(), Duration::from_millis(5 * steps * (steps + 1)));
+        }
+        assert_eq!(steps, 3);
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn stream_with_combinators() {
+        let f = &|_| Duration::from_millis(10);
+        let backoff = BackoffSequence::new(f);
+
+        let start = Instant::now();
+        assert_eq!(backoff.into_stream().take(4).collect::<Vec<_>>().await.len(), 4);
+        assert_eq!(start.elapsed(), Duration::from_millis(40));
+    }
+}
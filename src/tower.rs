@@ -0,0 +1,310 @@
+// Tower integration, enabled with the `tower` feature.  Delays are slept on the Tokio timer.
+
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use ::tokio::time::sleep;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::retry_async;
+use crate::{BackoffSequence, Calculator, Jitter, NoJitter};
+
+/// Decides whether a response from the inner service is worth retrying.
+pub trait RetryPolicy<R, E> {
+    fn retryable(&self, result: &Result<R, E>) -> bool;
+}
+
+/// The default policy, which retries every error and nothing else.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetryErrors;
+
+impl<R, E> RetryPolicy<R, E> for RetryErrors {
+    fn retryable(&self, result: &Result<R, E>) -> bool {
+        result.is_err()
+    }
+}
+
+impl<R, E, P> RetryPolicy<R, E> for P
+    where P: Fn(&Result<R, E>) -> bool
+{
+    fn retryable(&self, result: &Result<R, E>) -> bool {
+        self(result)
+    }
+}
+
+/// Hears about each request that's given up on once the sequence runs out, with the number of
+/// attempts and the last response, which is an `Ok` if the policy retries those.
+pub trait GiveUpHook<R, E> {
+    fn gave_up(&self, attempts: u64, result: &Result<R, E>);
+}
+
+/// The default hook, which does nothing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IgnoreGiveUp;
+
+impl<R, E> GiveUpHook<R, E> for IgnoreGiveUp {
+    fn gave_up(&self, _: u64, _: &Result<R, E>) {}
+}
+
+impl<R, E, G> GiveUpHook<R, E> for G
+    where G: Fn(u64, &Result<R, E>)
+{
+    fn gave_up(&self, attempts: u64, result: &Result<R, E>) {
+        self(attempts, result)
+    }
+}
+
+/// A `Layer` that retries the services it wraps, waiting for each value of the sequence in
+/// turn between attempts.  Like `BackoffSequence::retry`, `max_iterations` caps the number of
+/// attempts, and the request is always sent at least once.
+pub struct BackoffLayer<F: 'static, J = NoJitter, P = RetryErrors, G = IgnoreGiveUp> {
+    sequence: BackoffSequence<'static, F, Duration, J>,
+    policy: P,
+    giveup: G,
+}
+
+impl<F, J> BackoffLayer<F, J> {
+    pub fn new(sequence: BackoffSequence<'static, F, Duration, J>) -> Self {
+        BackoffLayer {
+            sequence,
+            policy: RetryErrors,
+            giveup: IgnoreGiveUp,
+        }
+    }
+}
+
+impl<F, J, P, G> BackoffLayer<F, J, P, G> {
+    /// Only retries when `policy` says so, which can be for an `Ok` response (like a 503) as
+    /// well as an error.
+    pub fn retry_if<Q>(self, policy: Q) -> BackoffLayer<F, J, Q, G> {
+        BackoffLayer {
+            sequence: self.sequence,
+            policy,
+            giveup: self.giveup,
+        }
+    }
+
+    /// Calls `hook` for each request that's given up on once the sequence runs out, like
+    /// `BackoffSequence::on_giveup`.  It isn't called on success, or for a response that the
+    /// policy doesn't retry.
+    pub fn on_giveup<H>(self, hook: H) -> BackoffLayer<F, J, P, H> {
+        BackoffLayer {
+            sequence: self.sequence,
+            policy: self.policy,
+            giveup: hook,
+        }
+    }
+}
+
+impl<F, J, P, G> Clone for BackoffLayer<F, J, P, G>
+    where J: Clone,
+          P: Clone,
+          G: Clone
+{
+    fn clone(&self) -> Self {
+        BackoffLayer {
+            sequence: self.sequence.clone(),
+            policy: self.policy.clone(),
+            giveup: self.giveup.clone(),
+        }
+    }
+}
+
+impl<S, F, J, P, G> Layer<S> for BackoffLayer<F, J, P, G>
+    where J: Clone,
+          P: Clone,
+          G: Clone
+{
+    type Service = BackoffService<S, F, J, P, G>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BackoffService {
+            inner,
+            sequence: self.sequence.clone(),
+            policy: self.policy.clone(),
+            giveup: self.giveup.clone(),
+        }
+    }
+}
+
+/// The service made by `BackoffLayer`.  Requests need to be `Clone`, so that each attempt can
+/// send its own copy.
+pub struct BackoffService<S, F: 'static, J = NoJitter, P = RetryErrors, G = IgnoreGiveUp> {
+    inner: S,
+    sequence: BackoffSequence<'static, F, Duration, J>,
+    policy: P,
+    giveup: G,
+}
+
+impl<S, F, J, P, G> Clone for BackoffService<S, F, J, P, G>
+    where S: Clone,
+          J: Clone,
+          P: Clone,
+          G: Clone
+{
+    fn clone(&self) -> Self {
+        BackoffService {
+            inner: self.inner.clone(),
+            sequence: self.sequence.clone(),
+            policy: self.policy.clone(),
+            giveup: self.giveup.clone(),
+        }
+    }
+}
+
+impl<S, Req, F, J, P, G> Service<Req> for BackoffService<S, F, J, P, G>
+    where S: Service<Req> + Clone + Send + 'static,
+          S::Future: Send,
+          Req: Clone + Send + 'static,
+          F: Calculator<Duration> + Send + Sync,
+          J: Jitter<Duration> + Clone + Send + 'static,
+          P: RetryPolicy<S::Response, S::Error> + Clone + Send + 'static,
+          G: GiveUpHook<S::Response, S::Error> + Clone + Send + 'static
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        // the inner service is ready now, so keep that one for the first attempt and leave the
+        // clone behind.  Later attempts each get a clone of their own, which is polled until
+        // it's ready, and an error from that counts as the attempt's error.
+        let clone = self.inner.clone();
+        let mut ready = Some(std::mem::replace(&mut self.inner, clone.clone()));
+        let sequence = self.sequence.clone();
+        let policy = self.policy.clone();
+        let giveup = self.giveup.clone();
+
+        let op = move || {
+            let (mut inner, fresh) = match ready.take() {
+                Some(inner) => (inner, false),
+                None => (clone.clone(), true),
+            };
+            let req = req.clone();
+            async move {
+                if fresh {
+                    poll_fn(|cx| inner.poll_ready(cx)).await?;
+                }
+                inner.call(req).await
+            }
+        };
+
+        Box::pin(async move {
+            retry_async::retry_results(sequence.iter(),
+                                       sequence.on_retry.as_ref(),
+                                       Some(sequence.time()),
+                                       op,
+                                       move |result| policy.retryable(result),
+                                       sleep,
+                                       move |attempts, result| giveup.gave_up(attempts, result))
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    use ::tokio::time::Instant;
+
+    // fails until it's been called `succeed_on` times, and answers with the number of calls
+    #[derive(Clone)]
+    struct Flaky {
+        calls: Arc<AtomicU64>,
+        succeed_on: u64,
+    }
+
+    impl Service<&'static str> for Flaky {
+        type Response = u64;
+        type Error = u64;
+        type Future = std::future::Ready<Result<u64, u64>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), u64>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: &'static str) -> Self::Future {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            std::future::ready(if calls < self.succeed_on { Err(calls) } else { Ok(calls) })
+        }
+    }
+
+    fn flaky(succeed_on: u64) -> Flaky {
+        Flaky {
+            calls: Arc::new(AtomicU64::new(0)),
+            succeed_on,
+        }
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn retries_errors() {
+        let mut backoff = BackoffSequence::linear_millis(10, 10);
+        backoff.max_iterations(5);
+        let mut service = BackoffLayer::new(backoff).layer(flaky(3));
+
+        let start = Instant::now();
+        assert_eq!(service.call("hello").await, Ok(3));
+        assert_eq!(start.elapsed(), Duration::from_millis(30));
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn gives_up_after_max_iterations() {
+        let mut backoff = BackoffSequence::constant(Duration::from_millis(10));
+        backoff.max_iterations(2);
+        let mut service = BackoffLayer::new(backoff).layer(flaky(10));
+        assert_eq!(service.call("hello").await, Err(2));
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn gives_up_once_per_request() {
+        let mut backoff = BackoffSequence::constant(Duration::from_millis(10));
+        backoff.max_iterations(2);
+        let gave_up = Arc::new(AtomicU64::new(0));
+        let counter = gave_up.clone();
+        let layer = BackoffLayer::new(backoff).on_giveup(move |n, res: &Result<u64, u64>| {
+            assert_eq!((n, res), (2, &Err(2)));
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert_eq!(layer.layer(flaky(10)).call("hello").await, Err(2));
+        assert_eq!(layer.layer(flaky(2)).call("hello").await, Ok(2));
+        assert_eq!(gave_up.load(Ordering::SeqCst), 1);
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn reports_each_retry() {
+        let mut backoff = BackoffSequence::constant(Duration::from_millis(10));
+        let retries = Arc::new(AtomicU64::new(0));
+        let counter = retries.clone();
+        backoff.max_iterations(5).on_retry(move |e| {
+            counter.store(e.attempt, Ordering::SeqCst);
+        });
+
+        let mut service = BackoffLayer::new(backoff).layer(flaky(3));
+        assert_eq!(service.call("hello").await, Ok(3));
+        assert_eq!(retries.load(Ordering::SeqCst), 2);
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn custom_policy() {
+        let mut backoff = BackoffSequence::constant(Duration::from_millis(10));
+        backoff.max_iterations(5);
+        let layer = BackoffLayer::new(backoff).retry_if(|res: &Result<u64, u64>| match res {
+            Ok(calls) => *calls < 4,
+            Err(_) => false,
+        });
+
+        assert_eq!(layer.layer(flaky(1)).call("hello").await, Ok(4));
+        assert_eq!(layer.layer(flaky(2)).call("hello").await, Err(1));
+    }
+}
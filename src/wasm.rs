@@ -0,0 +1,36 @@
+// Browser integration, enabled with the `wasm` feature.  There's no `thread::sleep` there, so
+// this waits on `setTimeout` through `gloo-timers` instead.
+
+use std::future::Future;
+use std::time::Duration;
+
+use gloo_timers::future::sleep;
+
+use crate::{retry, retry_async};
+use crate::{BackoffSequence, Calculator, Jitter};
+
+// the longest delay `setTimeout` can handle; anything longer would fire straight away
+const MAX_TIMEOUT: Duration = Duration::from_millis(i32::MAX as u64);
+
+/// Async retries that wait using the browser's `setTimeout`.
+pub trait RetryAsync {
+    /// The async version of `BackoffSequence::retry`: awaits `op` until it succeeds or the
+    /// sequence runs out, sleeping on a browser timer between attempts.  Delays longer than
+    /// `setTimeout` allows, about 24 days, are cut short to that.
+    fn retry_async<T, E, Fut, O>(&self, op: O) -> impl Future<Output = Result<T, E>>
+        where O: FnMut() -> Fut,
+              Fut: Future<Output = Result<T, E>>;
+}
+
+impl<'a, F, J> RetryAsync for BackoffSequence<'a, F, Duration, J>
+    where F: Calculator<Duration>,
+          J: Jitter<Duration> + Clone
+{
+    async fn retry_async<T, E, Fut, O>(&self, op: O) -> Result<T, E>
+        where O: FnMut() -> Fut,
+              Fut: Future<Output = Result<T, E>>
+    {
+        retry_async::retry(self, None, op, |delay| sleep(delay.min(MAX_TIMEOUT)), retry::ignore)
+            .await
+    }
+}